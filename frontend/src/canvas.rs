@@ -0,0 +1,14 @@
+use game::{Canvas, Color};
+use seed::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+
+/// Lets games draw on a browser canvas through their [`render`](game::Game::render) hook
+pub(crate) struct CanvasContext<'c>(pub(crate) &'c CanvasRenderingContext2d);
+
+impl Canvas for CanvasContext<'_> {
+    fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Color) {
+        let Color { r, g, b } = color;
+        self.0.set_fill_style(&JsValue::from_str(&format!("#{:02X}{:02X}{:02X}", r, g, b)));
+        self.0.fill_rect(x as f64, y as f64, width as f64, height as f64);
+    }
+}
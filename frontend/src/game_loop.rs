@@ -0,0 +1,230 @@
+use std::fmt::Debug;
+
+use seed::{*, prelude::*};
+use web_sys::{Event, HtmlCanvasElement, KeyboardEvent};
+
+use game::Game;
+
+use crate::GMsg;
+use crate::canvas::CanvasContext;
+
+// About 30 FPS
+const STEP_MILLI_SECONDS: u32 = 34;
+
+/// One or more games, that are played together in the browser
+///
+/// Every game gets its own card, e.g. the two fields of a split screen match. The
+/// [`GameLoop`] steps, draws and shows the games, the session only decides how key presses and
+/// steps are handled.
+pub(crate) trait Session {
+    type Game: Game;
+    /// Everything the session receives in one step, collected from the keys pressed since the
+    /// last step
+    type Input: Copy + Debug;
+
+    /// The input of a step, in which no key was pressed
+    const IDLE: Self::Input;
+
+    /// Applies a key press to the input of the next step
+    fn press(input: &mut Self::Input, key: &str);
+
+    /// Advances every game by one step, and returns whether the session is over
+    fn step(&mut self, input: Self::Input, orders: &mut impl Orders<GMsg>) -> bool;
+
+    /// The games, that are shown side by side
+    fn games(&self) -> Vec<&Self::Game>;
+
+    /// The header of the card of the game with the given index
+    fn title(&self, index: usize) -> String;
+
+    /// Starts over with new games
+    fn reset(&mut self);
+}
+
+/// Runs a [`Session`] at a fixed step rate and shows each of its games on a card
+pub(crate) struct GameLoop<S: Session> {
+    session: S,
+    canvases: Vec<ElRef<HtmlCanvasElement>>,
+    keyboard_listener: StreamHandle,
+    input: S::Input,
+    game_state: GameState,
+    // wraps the messages of this loop, so they reach it through the messages of the app
+    wrap: fn(Msg) -> GMsg,
+}
+
+pub(crate) enum Msg {
+    StartGame,
+    PauseGame,
+    ResetGame,
+    KeyBoardEvent(KeyboardEvent),
+    Render,
+}
+
+pub(crate) enum GameState {
+    Running,
+    Paused,
+    Over,
+    None,
+}
+
+impl<S: Session> GameLoop<S> {
+    pub(crate) fn new(session: S, wrap: fn(Msg) -> GMsg, orders: &mut impl Orders<GMsg>) -> Self {
+        let keyboard_listener = orders.stream_with_handle(
+            streams::window_event(Ev::KeyDown, move |ev: Event| {
+                wrap(Msg::KeyBoardEvent(ev.unchecked_into()))
+            })
+        );
+        window().focus();
+
+        let game_loop = Self {
+            canvases: Self::canvases(&session),
+            session,
+            keyboard_listener,
+            input: S::IDLE,
+            game_state: GameState::Running,
+            wrap,
+        };
+        game_loop.schedule_step(orders);
+        game_loop
+    }
+
+    pub(crate) fn session(&self) -> &S {
+        &self.session
+    }
+
+    pub(crate) fn update(&mut self, msg: Msg, orders: &mut impl Orders<GMsg>) {
+        match msg {
+            Msg::StartGame => {
+                if let GameState::None = self.game_state {
+                    self.game_state = GameState::Running;
+                    self.step(orders);
+                }
+            }
+            Msg::PauseGame => {
+                match self.game_state {
+                    GameState::Running => self.game_state = GameState::Paused,
+                    GameState::Paused => self.game_state = GameState::Running,
+                    GameState::Over | GameState::None => {}
+                }
+            }
+            Msg::ResetGame => {
+                self.session.reset();
+                self.canvases = Self::canvases(&self.session);
+                self.input = S::IDLE;
+                self.game_state = GameState::None;
+            }
+            Msg::KeyBoardEvent(ev) => S::press(&mut self.input, &ev.key()),
+            Msg::Render => {
+                self.step(orders);
+                self.draw_games();
+                self.schedule_step(orders);
+            }
+        }
+    }
+
+    /// One card for every game of the session
+    pub(crate) fn view(&self) -> Vec<Node<GMsg>> {
+        self.session
+            .games()
+            .into_iter()
+            .zip(&self.canvases)
+            .enumerate()
+            .map(|(index, (game, canvas))| {
+                view_card::<S::Game>(self.session.title(index), view_canvas::<S::Game>(canvas), footer(game))
+            })
+            .collect()
+    }
+
+    fn canvases(session: &S) -> Vec<ElRef<HtmlCanvasElement>> {
+        // cloned refs would share their element, so every canvas gets a new one
+        session.games().iter().map(|_| ElRef::new()).collect()
+    }
+
+    fn step(&mut self, orders: &mut impl Orders<GMsg>) {
+        log::trace!("step: {:?}", self.input);
+        let finished = self.session.step(self.input, orders);
+        self.input = S::IDLE;
+
+        if finished {
+            self.game_state = GameState::Over;
+        }
+    }
+
+    fn schedule_step(&self, orders: &mut impl Orders<GMsg>) {
+        if let GameState::Running = self.game_state {
+            let wrap = self.wrap;
+            orders
+                .perform_cmd(async move {
+                    cmds::timeout(STEP_MILLI_SECONDS, || {}).await;
+                    wrap(Msg::Render)
+                });
+        }
+    }
+
+    fn draw_games(&self) {
+        for (game, canvas) in self.session.games().into_iter().zip(&self.canvases) {
+            let canvas = canvas.get().expect("could not get canvas");
+            let ctx = seed::canvas_context_2d(&canvas);
+
+            ctx.clear_rect(0., 0., canvas.width() as f64, canvas.height() as f64);
+            game.render(&mut CanvasContext(&ctx));
+        }
+    }
+}
+
+/// The scores of all players of the game, e.g. "3 : 5" in a game for two players
+fn footer<G: Game>(game: &G) -> String {
+    match game.players() {
+        1 => format!("Score: {}", game.score(0)),
+        players => (0..players)
+            .map(|player| game.score(player).to_string())
+            .collect::<Vec<_>>()
+            .join(" : "),
+    }
+}
+
+/// A canvas in the resolution of the game, that fills its card
+fn view_canvas<G: Game>(canvas: &ElRef<HtmlCanvasElement>) -> Node<GMsg> {
+    canvas![
+        el_ref(canvas),
+        C!["d-block", "position-absolute", "w-100", "h-100"],
+        style! {
+            St::Top => "0",
+            St::Left => "0",
+        },
+        attrs! {
+            At::Width => G::WIDTH,
+            At::Height => G::HEIGHT,
+        },
+    ]
+}
+
+/// A card, whose body keeps the aspect ratio of the game
+fn view_card<G: Game>(header: String, game: Node<GMsg>, footer: String) -> Node<GMsg> {
+    div![
+        C![
+            "col", "card", "p-0", "mx-3", "bg-light", "border", "border-light", "border-3", "rounded-3",
+            "shadow-lg", "text-center"
+        ],
+        style! { St::MaxWidth => format!("calc(70vh * {} / {})", G::WIDTH, G::HEIGHT) },
+
+        div![
+            C!["card-header", "bg-light"],
+            header
+        ],
+        div![
+            C!["card-body", "position-relative"],
+            style! {
+                St::Background => "#000",
+                St::Height => "0",
+                St::Overflow => "hidden",
+                St::PaddingTop => format!("calc({} / {} * 100%)", G::HEIGHT, G::WIDTH),
+            },
+            game
+        ],
+        div![
+            C!["card-footer", "bg-light"],
+            footer
+        ]
+    ]
+}
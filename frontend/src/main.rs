@@ -5,6 +5,12 @@
 
 use seed::{*, prelude::*};
 
+use crate::game_loop::GameLoop;
+use crate::space_invaders::Mode;
+
+mod canvas;
+mod game_loop;
+mod pong;
 mod space_invaders;
 
 #[global_allocator]
@@ -12,35 +18,60 @@ static ALLOC: wee_alloc::WeeAlloc<'_> = wee_alloc::WeeAlloc::INIT;
 
 struct Model {
     page: Page,
-    space_invaders: crate::space_invaders::Model,
 }
 
 enum GMsg {
-    SpaceInvaders(crate::space_invaders::Msg)
+    SpaceInvaders(game_loop::Msg),
+    Pong(game_loop::Msg),
+    ShowGame(GameKind),
 }
 
 enum Page {
     Home,
-    SpaceInvaders,
+    SpaceInvaders(GameLoop<crate::space_invaders::Session>),
+    Pong(GameLoop<::pong::PlayField>),
+}
+
+#[derive(Clone, Copy)]
+enum GameKind {
+    SpaceInvaders(Mode),
+    Pong,
 }
 
 impl Model {
     fn init(_url: Url, orders: &mut impl Orders<GMsg>) -> Self {
         Self {
-            // todo
-            page: Page::SpaceInvaders,
-            space_invaders: crate::space_invaders::Model::new(orders),
+            page: Self::space_invaders(Mode::SinglePlayer, orders),
         }
     }
 
     fn update(msg: GMsg, model: &mut Self, orders: &mut impl Orders<GMsg>) {
-        match msg {
-            GMsg::SpaceInvaders(msg) => {
-                model.space_invaders.update(msg, orders);
+        // messages of a game, that is not shown anymore, are dropped
+        match (msg, &mut model.page) {
+            (GMsg::SpaceInvaders(msg), Page::SpaceInvaders(space_invaders)) => {
+                space_invaders.update(msg, orders);
+            }
+            (GMsg::Pong(msg), Page::Pong(pong)) => {
+                pong.update(msg, orders);
+            }
+            // the game is already shown
+            (GMsg::ShowGame(GameKind::SpaceInvaders(mode)), Page::SpaceInvaders(space_invaders))
+                if space_invaders.session().mode() == mode => {}
+            (GMsg::ShowGame(GameKind::Pong), Page::Pong(_)) => {}
+            (GMsg::ShowGame(GameKind::SpaceInvaders(mode)), _) => {
+                model.page = Self::space_invaders(mode, orders);
+            }
+            (GMsg::ShowGame(GameKind::Pong), _) => {
+                model.page = Page::Pong(GameLoop::new(::pong::PlayField::new(), GMsg::Pong, orders));
             }
+            _ => {}
         }
     }
 
+    fn space_invaders(mode: Mode, orders: &mut impl Orders<GMsg>) -> Page {
+        Page::SpaceInvaders(GameLoop::new(crate::space_invaders::Session::new(mode), GMsg::SpaceInvaders, orders))
+    }
+
     fn view(&self) -> Vec<Node<GMsg>> {
         nodes![
             Self::view_header(),
@@ -60,12 +91,27 @@ impl Model {
                         attrs! { At::Href => "#" },
                         "Competitive AI",
                         
+                    ],
+                    div![
+                        C!["navbar-nav", "flex-row"],
+                        Self::view_nav_link("Space Invaders", GameKind::SpaceInvaders(Mode::SinglePlayer)),
+                        Self::view_nav_link("Split Screen", GameKind::SpaceInvaders(Mode::SplitScreen)),
+                        Self::view_nav_link("Pong", GameKind::Pong),
                     ]
                 ]
             ]
         ]
     }
 
+    fn view_nav_link(name: &str, game: GameKind) -> Node<GMsg> {
+        a![
+            C!["nav-link", "ms-3"],
+            attrs! { At::Href => "#" },
+            name,
+            ev(Ev::Click, move |_| GMsg::ShowGame(game)),
+        ]
+    }
+
     fn view_main(&self) -> Node<GMsg> {
         main![
             C!["container-fluid", "bg-secondary", "p-0"],
@@ -73,43 +119,15 @@ impl Model {
             
             div![
                 C!["row", "m-0", "w-100", "h-100", "d-flex", "align-items-center", "justify-content-evenly"],
-                (1..=1).map(|i| self.view_team_card(i))
+                match &self.page {
+                    Page::Home => vec![],
+                    Page::SpaceInvaders(space_invaders) => space_invaders.view(),
+                    Page::Pong(pong) => pong.view(),
+                }
             ],
         ]
     }
 
-    fn view_team_card(&self, team: u8) -> Node<GMsg> {
-        div![
-            C![
-                "col", "card", "p-0", "mx-3", "bg-light", "border", "border-light", "border-3", "rounded-3",
-                "shadow-lg", "text-center"
-            ],
-            style! { St::MaxWidth => "60vh" }, 
-             
-            div![
-                C!["card-header", "bg-light"],
-                "Team ",
-                team.to_string()
-            ],
-            div![
-                C!["card-body", "position-relative"],
-                style! {
-                    St::Background => "#000",
-                    St::Height => "0",
-                    St::Overflow => "hidden",
-                    St::PaddingTop => "calc(4 / 3 * 100%)" 
-                },
-                self.space_invaders
-                    .view()
-                    .map_msg(GMsg::SpaceInvaders)
-            ],
-            div![
-                C!["card-footer", "bg-light"],
-                "footer"
-            ]
-        ]
-    }
-
     fn view_footer() -> Node<GMsg> {
         footer![
             nav![
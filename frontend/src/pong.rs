@@ -0,0 +1,47 @@
+use seed::prelude::*;
+
+use pong::{Instruction, PlayField, Player};
+
+use crate::GMsg;
+use crate::game_loop::Session;
+
+/// Both players share one keyboard, the left player plays with W and S, the right player with
+/// the arrow keys
+impl Session for PlayField {
+    type Game = PlayField;
+    type Input = [Instruction; 2];
+
+    const IDLE: Self::Input = [Instruction::None; 2];
+
+    fn press(input: &mut Self::Input, key: &str) {
+        match key {
+            "w" => input[Player::Left.index()] = Instruction::MoveUp,
+            "s" => input[Player::Left.index()] = Instruction::MoveDown,
+            "ArrowUp" => input[Player::Right.index()] = Instruction::MoveUp,
+            "ArrowDown" => input[Player::Right.index()] = Instruction::MoveDown,
+            _ => {}
+        }
+    }
+
+    fn step(&mut self, input: Self::Input, _orders: &mut impl Orders<GMsg>) -> bool {
+        let status = PlayField::step(self, input);
+        log::trace!("status: {:?}", status);
+
+        if status.is_done() {
+            log::info!("game finished: {:?}", status);
+        }
+        status.is_done()
+    }
+
+    fn games(&self) -> Vec<&Self::Game> {
+        vec![self]
+    }
+
+    fn title(&self, _index: usize) -> String {
+        "Pong".to_owned()
+    }
+
+    fn reset(&mut self) {
+        *self = PlayField::new();
+    }
+}
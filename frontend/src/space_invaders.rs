@@ -1,320 +1,205 @@
-use std::rc::Rc;
-
 use seed::{*, prelude::*};
-use web_sys::{CanvasRenderingContext2d, Event, HtmlCanvasElement, KeyboardEvent};
 
-use space_invaders::{GameObj, Instruction, PlayField, Position, Unit};
-use space_invaders::alien::{Alien, Aliens, AlienType};
-use space_invaders::bullet::Bullet;
-use space_invaders::bunker::{Bunker, Bunkers};
-use space_invaders::cannon::Cannon;
+use space_invaders::{Input, Instruction, PlayField, Player};
+use space_invaders::config::GameConfig;
+use space_invaders::event::Event as GameEvent;
+use space_invaders::replay::Replay;
+use space_invaders::split_screen::Match;
 
 use crate::GMsg;
+use crate::game_loop;
 
-// About 30 FPS 
-const STEP_MILLI_SECONDS: u32 = 34;
+const REPLAY_URL: &str = "/api/replays";
 
 const KEY_ARROW_LEFT: &str = "ArrowLeft";
 const KEY_ARROW_RIGHT: &str = "ArrowRight";
 const KEY_ARROW_SPACE: &str = " ";
 
-thread_local! {
-    static RED: Rc<JsValue> = Rc::new(JsValue::from_str("#FF6000"));
-    static DBG_RED: Rc<JsValue> = Rc::new(JsValue::from_str("#F00"));
-    static GREEN: Rc<JsValue> = Rc::new(JsValue::from_str("#1BBE81"));
-    static WHITE: Rc<JsValue> = Rc::new(JsValue::from_str("#fff"));
-}
-
+const IDLE: Input = Input { instruction: Instruction::None, shoot: false };
 
-pub(crate) struct Model {
-    play_field: PlayField,
-    canvas: ElRef<HtmlCanvasElement>,
-    keyboard_listener: StreamHandle,
-    instruction: Instruction,
-    shoot: bool,
-    game_state: GameState,
+/// The fields of one mode, and one replay for every field
+pub(crate) struct Session {
+    fields: Fields,
+    replays: Vec<Replay>,
 }
 
-pub(crate) enum Msg {
-    StartGame,
-    PauseGame,
-    ResetGame,
-    KeyBoardEvent(KeyboardEvent),
-    Render,
+/// How many people play at once
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Mode {
+    SinglePlayer,
+    /// Two players on two fields with the same seed, see [`Match`]
+    SplitScreen,
 }
 
-pub(crate) enum GameState {
-    Running,
-    Paused,
-    None,
+enum Fields {
+    SinglePlayer(PlayField),
+    SplitScreen(Match),
 }
 
-impl Model {
-    pub(crate) fn new(orders: &mut impl Orders<GMsg>) -> Self {
-        let keyboard_listener = orders.stream_with_handle(
-            streams::window_event(Ev::KeyDown, |ev: Event| {
-                GMsg::SpaceInvaders(Msg::KeyBoardEvent(ev.unchecked_into()))
-            })
-        );
-        window().focus();
-
-        let model = Self {
-            play_field: PlayField::new(),
-            canvas: ElRef::new(),
-            keyboard_listener,
-            instruction: Instruction::None,
-            shoot: false,
-            game_state: GameState::Running,
-        };
-        model.schedule_step(orders);
-        model
-    }
-
-    pub(crate) fn update(&mut self, msg: Msg, orders: &mut impl Orders<GMsg>) {
-        match msg {
-            Msg::StartGame => {
-                if let GameState::None = self.game_state {
-                    self.game_state = GameState::Running;
-                    self.step();
-                }
-            }
-            Msg::PauseGame => {
-                match self.game_state {
-                    GameState::Running => self.game_state = GameState::Paused,
-                    GameState::Paused => self.game_state = GameState::Running,
-                    GameState::None => {}
-                }
-            }
-            Msg::ResetGame => {
-                self.play_field = PlayField::new();
-                self.game_state = GameState::None;
-            }
-            Msg::KeyBoardEvent(ev) => {
-                match &*ev.key() {
-                    "ArrowLeft" => self.instruction = Instruction::MoveLeft,
-                    "ArrowRight" => self.instruction = Instruction::MoveRight,
-                    " " => self.shoot = true,
-                    _ => {}
-                }
-            }
-            Msg::Render => {
-                self.step();
-                self.draw_play_field();
-                self.schedule_step(orders);
-            }
+impl Session {
+    pub(crate) fn new(mode: Mode) -> Self {
+        let fields = Fields::new(mode);
+        Self {
+            replays: fields.replays(),
+            fields,
         }
     }
 
-    pub(crate) fn view(&self) -> Node<Msg> {
-        canvas![
-            el_ref(&self.canvas),
-            C!["d-block", "position-absolute", "w-100", "h-100"],
-            style! {
-                St::Top => "0",
-                St::Left => "0",
-            },
-            attrs! {
-                At::Width => PlayField::WIDTH,
-                At::Height => PlayField::HEIGHT,
-            },
-        ]
-    }
-
-    fn step(&mut self) {
-        log::trace!("step: {:?} | shoot: {}", self.instruction, self.shoot);
-        let survived = self.play_field.step(self.instruction, self.shoot);
-        log::trace!("player survived: {}", survived);
-        self.instruction = Instruction::None;
-        self.shoot = false;
-    }
-
-    fn schedule_step(&self, orders: &mut impl Orders<GMsg>) {
-        if let GameState::Running = self.game_state {
-            orders
-                .perform_cmd(async {
-                    cmds::timeout(STEP_MILLI_SECONDS, || {}).await;
-                    GMsg::SpaceInvaders(Msg::Render)
-                });
+    pub(crate) fn mode(&self) -> Mode {
+        match self.fields {
+            Fields::SinglePlayer(_) => Mode::SinglePlayer,
+            Fields::SplitScreen(_) => Mode::SplitScreen,
         }
     }
 
-    fn draw_play_field(&self) {
-        let canvas = self.canvas.get().expect("could not get canvas");
-        let ctx = seed::canvas_context_2d(&canvas);
-
-        Self::clear_canvas(&canvas, &ctx);
-        Self::draw_bullets(&ctx, self.play_field.bullets());
-        Self::draw_aliens(&ctx, self.play_field.aliens());
-        Self::draw_bunkers(&ctx, self.play_field.bunkers());
-        Self::draw_cannon(&ctx, self.play_field.cannon().position());
-    }
-
-    fn clear_canvas(canvas: &HtmlCanvasElement, ctx: &CanvasRenderingContext2d) {
-        ctx.clear_rect(0., 0., canvas.width() as f64, canvas.height() as f64);
-    }
-
-    fn draw_aliens(ctx: &CanvasRenderingContext2d, aliens: &Aliens) {
-        Self::draw_dbg::<Aliens>(ctx, aliens.position().x, aliens.position().y);
-        aliens
-            .iter()
-            .map(|col| col.iter())
-            .flatten()
-            .for_each(|alien| {
-                if let Some(alien) = alien {
-                    Self::draw_alien(ctx, alien);
-                }
-            })
-    }
-
-    fn draw_alien(ctx: &CanvasRenderingContext2d, alien: &Alien) {
-        WHITE.with(|white| {
-            ctx.set_fill_style(white)
-        });
-
-        match alien.alien_type() {
-            AlienType::Mystery => Self::draw_alien_mystery(ctx, alien.position()),
-            AlienType::Hard => Self::draw_alien_hard(ctx, alien.position()),
-            AlienType::Medium => Self::draw_alien_medium(ctx, alien.position()),
-            AlienType::Easy => Self::draw_alien_easy(ctx, alien.position()),
+    /// The players, that have a field in the current mode
+    fn players(&self) -> &'static [Player] {
+        match self.fields {
+            Fields::SinglePlayer(_) => &[Player::One],
+            Fields::SplitScreen(_) => &Player::BOTH,
         }
     }
 
-    fn draw_alien_hard(ctx: &CanvasRenderingContext2d, Position { mut x, y }: Position) {
-        Self::draw_dbg::<Alien>(ctx, x, y);
-        // hard aliens are four pixels smaller then easy aliens
-        // space invaders centers them, so we do the same
-        x += 2;
-
-        ctx.fill_rect(x as f64 + 3., y as f64 + 0., 2., 1.);
-        ctx.fill_rect(x as f64 + 2., y as f64 + 1., 4., 1.);
-        ctx.fill_rect(x as f64 + 1., y as f64 + 2., 6., 1.);
-        ctx.fill_rect(x as f64 + 0., y as f64 + 3., 2., 2.);
-        ctx.fill_rect(x as f64 + 3., y as f64 + 3., 2., 4.);
-        ctx.fill_rect(x as f64 + 6., y as f64 + 3., 2., 2.);
-        ctx.fill_rect(x as f64 + 2., y as f64 + 4., 4., 1.);
-        ctx.fill_rect(x as f64 + 1., y as f64 + 5., 1., 1.);
-        ctx.fill_rect(x as f64 + 0., y as f64 + 6., 1., 1.);
-        ctx.fill_rect(x as f64 + 1., y as f64 + 7., 1., 1.);
-        ctx.fill_rect(x as f64 + 6., y as f64 + 5., 1., 1.);
-        ctx.fill_rect(x as f64 + 7., y as f64 + 6., 1., 1.);
-        ctx.fill_rect(x as f64 + 6., y as f64 + 7., 1., 1.);
+    /// Sends the replay to the server, which verifies and stores it
+    fn upload_replay(replay: &Replay, orders: &mut impl Orders<GMsg>) {
+        let bytes = match replay.to_bytes() {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                log::error!("could not encode replay: {}", err);
+                return;
+            }
+        };
+        log::info!("replay: {} steps | {} bytes", replay.inputs().len(), bytes.len());
+
+        orders.perform_cmd(async move {
+            let response = async {
+                Request::new(REPLAY_URL)
+                    .method(Method::Post)
+                    .body(js_sys::Uint8Array::from(&bytes[..]).into())
+                    .fetch()
+                    .await?
+                    .check_status()?
+                    .text()
+                    .await
+            };
+
+            match response.await {
+                Ok(name) => log::info!("replay stored as {}", name),
+                Err(err) => log::error!("could not upload replay: {:?}", err),
+            }
+        });
     }
+}
 
-    fn draw_alien_medium(ctx: &CanvasRenderingContext2d, Position { mut x, y }: Position) {
-        Self::draw_dbg::<Alien>(ctx, x, y);
-        // medium aliens are one pixel smaller then easy aliens
-        // space invaders aligns them to the right, so we do the same
-        x += 1;
+/// Player one plays with the arrow keys and space, player two with A, D and W
+impl game_loop::Session for Session {
+    type Game = PlayField;
+    type Input = [Input; 2];
+
+    const IDLE: Self::Input = [IDLE; 2];
+
+    fn press(inputs: &mut Self::Input, key: &str) {
+        let (player, instruction, shoot) = match key {
+            KEY_ARROW_LEFT => (Player::One, Instruction::MoveLeft, false),
+            KEY_ARROW_RIGHT => (Player::One, Instruction::MoveRight, false),
+            KEY_ARROW_SPACE => (Player::One, Instruction::None, true),
+            "a" => (Player::Two, Instruction::MoveLeft, false),
+            "d" => (Player::Two, Instruction::MoveRight, false),
+            "w" => (Player::Two, Instruction::None, true),
+            _ => return,
+        };
 
-        ctx.fill_rect(x as f64 + 2., y as f64 + 0., 1., 1.);
-        ctx.fill_rect(x as f64 + 3., y as f64 + 1., 1., 1.);
-        ctx.fill_rect(x as f64 + 8., y as f64 + 0., 1., 1.);
-        ctx.fill_rect(x as f64 + 7., y as f64 + 1., 1., 1.);
-        ctx.fill_rect(x as f64 + 2., y as f64 + 2., 7., 1.);
-        ctx.fill_rect(x as f64 + 1., y as f64 + 3., 2., 1.);
-        ctx.fill_rect(x as f64 + 4., y as f64 + 3., 3., 1.);
-        ctx.fill_rect(x as f64 + 8., y as f64 + 3., 2., 1.);
-        ctx.fill_rect(x as f64 + 0., y as f64 + 4., 11., 2.);
-        ctx.fill_rect(x as f64 + 0., y as f64 + 6., 1., 1.);
-        ctx.fill_rect(x as f64 + 2., y as f64 + 6., 1., 1.);
-        ctx.fill_rect(x as f64 + 8., y as f64 + 6., 1., 1.);
-        ctx.fill_rect(x as f64 + 10., y as f64 + 6., 1., 1.);
-        ctx.fill_rect(x as f64 + 3., y as f64 + 7., 2., 1.);
-        ctx.fill_rect(x as f64 + 6., y as f64 + 7., 2., 1.);
+        let input = &mut inputs[player.index()];
+        match shoot {
+            true => input.shoot = true,
+            false => input.instruction = instruction,
+        }
     }
 
-    fn draw_alien_easy(ctx: &CanvasRenderingContext2d, Position { x, y }: Position) {
-        Self::draw_dbg::<Alien>(ctx, x, y);
-
-        ctx.fill_rect(x as f64 + 4., y as f64 + 0., 4., 1.);
-        ctx.fill_rect(x as f64 + 1., y as f64 + 1., 10., 2.);
-        ctx.fill_rect(x as f64 + 0., y as f64 + 2., 3., 3.);
-        ctx.fill_rect(x as f64 + 5., y as f64 + 3., 2., 1.);
-        ctx.fill_rect(x as f64 + 9., y as f64 + 2., 3., 3.);
-        ctx.fill_rect(x as f64 + 3., y as f64 + 4., 6., 1.);
-        ctx.fill_rect(x as f64 + 2., y as f64 + 5., 3., 1.);
-        ctx.fill_rect(x as f64 + 7., y as f64 + 5., 3., 1.);
-        ctx.fill_rect(x as f64 + 1., y as f64 + 6., 2., 1.);
-        ctx.fill_rect(x as f64 + 5., y as f64 + 6., 2., 1.);
-        ctx.fill_rect(x as f64 + 9., y as f64 + 6., 2., 1.);
-        ctx.fill_rect(x as f64 + 2., y as f64 + 7., 2., 1.);
-        ctx.fill_rect(x as f64 + 8., y as f64 + 7., 2., 1.);
-    }
+    fn step(&mut self, inputs: Self::Input, orders: &mut impl Orders<GMsg>) -> bool {
+        let finished = match &mut self.fields {
+            Fields::SinglePlayer(play_field) => {
+                let Input { instruction, shoot } = inputs[Player::One.index()];
+                let status = play_field.step(instruction, shoot);
+                log::trace!("status: {:?}", status);
+                if status.is_done() {
+                    log::info!("game finished: {:?} | score: {}", status, play_field.score());
+                }
+                status.is_done()
+            }
+            Fields::SplitScreen(game) => {
+                let status = game.step(inputs);
+                log::trace!("status: {:?}", status);
+                let result = game.result();
+                if let Some(result) = &result {
+                    log::info!("match finished: {:?} | {:?}", status, result);
+                }
+                result.is_some()
+            }
+        };
 
-    fn draw_alien_mystery(ctx: &CanvasRenderingContext2d, Position { x, y }: Position) {
-        RED.with(|red| {
-            ctx.set_fill_style(red)
-        });
-        Self::draw_dbg::<Alien>(ctx, x, y);
+        for &player in self.players() {
+            let input = inputs[player.index()];
+            self.fields
+                .drain_events(player)
+                .for_each(|event| log::debug!("event {:?}: {:?}", player, event));
+            self.replays[player.index()].record(self.fields.play_field(player), input.instruction, input.shoot);
+        }
 
-        todo!()
+        if finished {
+            for replay in &self.replays {
+                Self::upload_replay(replay, orders);
+            }
+        }
+        finished
     }
 
-    fn draw_bunkers(ctx: &CanvasRenderingContext2d, bunkers: &Bunkers) {
-        GREEN.with(|green| {
-            ctx.set_fill_style(green)
-        });
-        Self::draw_dbg::<Bunkers>(ctx, bunkers.position().x, bunkers.position().y);
-
-        bunkers
+    fn games(&self) -> Vec<&Self::Game> {
+        self.players()
             .iter()
-            .for_each(|bunker| {
-                if let Some(bunker) = bunker {
-                    Self::draw_bunker(ctx, bunker.position())
-                }
-            })
+            .map(|&player| self.fields.play_field(player))
+            .collect()
     }
 
-    fn draw_bunker(ctx: &CanvasRenderingContext2d, Position { x, y }: Position) {
-        Self::draw_dbg::<Bunker>(ctx, x, y);
-        ctx.fill_rect(x as f64 + 3., y as f64 + 0., 18., 1.);
-        ctx.fill_rect(x as f64 + 2., y as f64 + 1., 20., 1.);
-        ctx.fill_rect(x as f64 + 1., y as f64 + 2., 22., 1.);
-        ctx.fill_rect(x as f64 + 0., y as f64 + 3., 6., 15.);
-        ctx.fill_rect(x as f64 + 18., y as f64 + 3., 6., 15.);
-        ctx.fill_rect(x as f64 + 6., y as f64 + 3., 12., 5.);
-        ctx.fill_rect(x as f64 + 6., y as f64 + 8., 4., 1.);
-        ctx.fill_rect(x as f64 + 6., y as f64 + 9., 3., 1.);
-        ctx.fill_rect(x as f64 + 6., y as f64 + 10., 2., 1.);
-        ctx.fill_rect(x as f64 + 6., y as f64 + 11., 1., 1.);
-        ctx.fill_rect(x as f64 + 14., y as f64 + 8., 4., 1.);
-        ctx.fill_rect(x as f64 + 15., y as f64 + 9., 3., 1.);
-        ctx.fill_rect(x as f64 + 16., y as f64 + 10., 2., 1.);
-        ctx.fill_rect(x as f64 + 17., y as f64 + 11., 1., 1.);
+    fn title(&self, index: usize) -> String {
+        format!("Team {}", index + 1)
     }
 
-    fn draw_cannon(ctx: &CanvasRenderingContext2d, Position { x, y }: Position) {
-        GREEN.with(|white| {
-            ctx.set_fill_style(white)
-        });
-        Self::draw_dbg::<Cannon>(ctx, x, y);
-
-        ctx.fill_rect(x as f64 + 7., y as f64 + 0., 1., 1.);
-        ctx.fill_rect(x as f64 + 6., y as f64 + 1., 3., 2.);
-        ctx.fill_rect(x as f64 + 1., y as f64 + 3., 13., 1.);
-        ctx.fill_rect(x as f64 + 0., y as f64 + 4., 15., 4.);
+    fn reset(&mut self) {
+        *self = Self::new(self.mode());
     }
+}
 
-    fn draw_bullets(ctx: &CanvasRenderingContext2d, bullets: &Vec<Bullet>) {
-        WHITE.with(|white| {
-            ctx.set_fill_style(white)
-        });
+impl Fields {
+    fn new(mode: Mode) -> Self {
+        match mode {
+            Mode::SinglePlayer => Self::SinglePlayer(PlayField::new()),
+            Mode::SplitScreen => Self::SplitScreen(Match::new(GameConfig::default())),
+        }
+    }
 
-        bullets
-            .iter()
-            .for_each(|bullet| Self::draw_bullet(ctx, bullet.position()));
+    fn play_field(&self, player: Player) -> &PlayField {
+        match self {
+            Self::SinglePlayer(play_field) => play_field,
+            Self::SplitScreen(game) => game.play_field(player),
+        }
     }
 
-    fn draw_bullet(ctx: &CanvasRenderingContext2d, Position { x, y }: Position) {
-        ctx.fill_rect(x as f64, y as f64, 1., 3.);
+    fn drain_events(&mut self, player: Player) -> std::vec::Drain<'_, GameEvent> {
+        match self {
+            Self::SinglePlayer(play_field) => play_field.drain_events(),
+            Self::SplitScreen(game) => game.drain_events(player),
+        }
     }
 
-    fn draw_dbg<T: GameObj>(ctx: &CanvasRenderingContext2d, x: Unit, y: Unit) {
-        DBG_RED.with(|red| ctx.set_stroke_style(red));
-        ctx.stroke_rect(x as f64, y as f64, T::WIDTH as f64, T::HEIGHT as f64);
+    /// One empty replay for every field
+    fn replays(&self) -> Vec<Replay> {
+        match self {
+            Self::SinglePlayer(play_field) => vec![Replay::new(play_field)],
+            Self::SplitScreen(game) => Player::BOTH
+                .iter()
+                .map(|&player| Replay::new(game.play_field(player)))
+                .collect(),
+        }
     }
 }
-
-
-
-
@@ -0,0 +1,182 @@
+//! The interfaces every game of the platform implements
+//!
+//! The server, the tournament logic and the frontend only talk to games through these traits,
+//! so hosting a new game doesn't require any changes to them. The games also share the building
+//! blocks of their play fields, like [`Position`], [`GameObj`] and [`GameRng`].
+
+#![no_std]
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::rng::GameRng;
+
+mod rng;
+
+pub type Unit = usize;
+pub type Score = i64;
+pub type Reward = f64;
+
+/// The top left corner of a game object, or a cell of a grid
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Position {
+    pub x: Unit,
+    pub y: Unit,
+}
+
+impl Position {
+    /// Whether the rectangles at `self` and `other` share at least one pixel
+    pub const fn overlaps(
+        &self,
+        self_width: Unit,
+        self_height: Unit,
+        other: Position,
+        other_width: Unit,
+        other_height: Unit,
+    ) -> bool {
+        let overlaps_on_x = self.x < other.x + other_width && other.x < self.x + self_width;
+        let overlaps_on_y = self.y < other.y + other_height && other.y < self.y + self_height;
+
+        overlaps_on_x && overlaps_on_y
+    }
+
+    /// The number of steps between two positions, when moving only horizontally and vertically
+    pub fn distance(&self, other: Position) -> Unit {
+        (self.x as isize - other.x as isize).unsigned_abs() + (self.y as isize - other.y as isize).unsigned_abs()
+    }
+}
+
+/// Anything, that occupies a rectangle of a play field
+pub trait GameObj {
+    const WIDTH: Unit;
+    const HEIGHT: Unit;
+
+    fn position(&self) -> Position;
+
+    fn overlaps<O: GameObj>(&self, other: &O) -> bool {
+        self
+            .position()
+            .overlaps(
+                Self::WIDTH,
+                Self::HEIGHT,
+                other.position(),
+                O::WIDTH,
+                O::HEIGHT,
+            )
+    }
+}
+
+/// Anything, that moves on its own
+pub trait Step {
+    fn step(&mut self, rng: &mut GameRng) -> StepResult;
+}
+
+pub struct StepResult {
+    /// Whether the object is still on the play field
+    pub survived: bool,
+}
+
+impl Default for StepResult {
+    fn default() -> Self {
+        Self { survived: true }
+    }
+}
+
+/// A deterministic game for one or more players
+pub trait Game {
+    /// Everything an agent can see of the game
+    type Observation;
+    /// Everything the game receives in one step
+    ///
+    /// In games with multiple players, the action contains the inputs of all players.
+    type Action;
+    type Status: Status;
+
+    /// The width of the rendered game
+    const WIDTH: usize;
+    /// The height of the rendered game
+    const HEIGHT: usize;
+
+    /// Advances the game by one step
+    fn step(&mut self, action: Self::Action) -> Self::Status;
+
+    /// The status returned by the last step
+    fn status(&self) -> Self::Status;
+
+    fn observe(&self) -> Self::Observation;
+
+    fn players(&self) -> usize {
+        1
+    }
+
+    /// The score of the player with the given index
+    ///
+    /// # Panics
+    ///
+    /// Panics, if there is no such player.
+    fn score(&self, player: usize) -> Score;
+
+    /// Draws the current state of the game
+    fn render(&self, canvas: &mut dyn Canvas);
+}
+
+/// The status of a [`Game`]
+pub trait Status: Copy {
+    /// Whether the game is over and doesn't change anymore
+    fn is_done(&self) -> bool;
+}
+
+/// A surface, that games can draw on
+///
+/// The frontend implements it for the browser canvas, and each game has a headless pixel buffer
+/// for agents, that learn from pixels.
+pub trait Canvas {
+    /// Fills a rectangle in the coordinates of the game, clipped to the canvas
+    fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Color);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const BLACK: Self = Self { r: 0x00, g: 0x00, b: 0x00 };
+    pub const WHITE: Self = Self { r: 0xFF, g: 0xFF, b: 0xFF };
+    pub const RED: Self = Self { r: 0xFF, g: 0x60, b: 0x00 };
+    pub const GREEN: Self = Self { r: 0x1B, g: 0xBE, b: 0x81 };
+
+    /// The luminance of the color (ITU-R BT.601)
+    pub fn gray(&self) -> u8 {
+        ((self.r as u32 * 299 + self.g as u32 * 587 + self.b as u32 * 114) / 1000) as u8
+    }
+}
+
+/// A gym-style interface for training agents
+pub trait Environment {
+    type Observation;
+    type Action;
+    type Info;
+
+    /// Starts a new episode, whose randomness is fully determined by `seed`
+    fn reset(&mut self, seed: u64) -> Self::Observation;
+
+    /// Advances the episode by one step
+    ///
+    /// Returns the new observation, the reward for `action`, whether the episode is done, and
+    /// additional information, that is not meant to be used by the agent.
+    fn step(&mut self, action: Self::Action) -> (Self::Observation, Reward, bool, Self::Info);
+
+    fn action_space(&self) -> ActionSpace;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ActionSpace {
+    /// The actions are numbered from `0` to `n - 1`
+    Discrete(usize),
+}
@@ -0,0 +1,96 @@
+use rand_core::{impls, le, Error, RngCore, SeedableRng};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+// the default multiplier of PCG for a 64 bit state
+const MULTIPLIER: u64 = 6364136223846793005;
+
+/// The random number generator used for all randomness in the games
+///
+/// This is the standard PCG32 (XSH RR 64/32), so it produces exactly the same numbers as
+/// `rand_pcg::Pcg32`. PCG32 is portable, so the same seed produces the same game on every platform,
+/// including wasm32. Unlike `rand_pcg::Pcg32`, the whole state takes part in [`Hash`], so a
+/// checksum of a game also covers the position in the random stream.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameRng {
+    state: u64,
+    increment: u64,
+}
+
+impl GameRng {
+    /// Creates a generator with the initial `state` on the stream `stream`
+    pub fn new(state: u64, stream: u64) -> Self {
+        // the increment has to be odd
+        Self::from_state_increment(state, (stream << 1) | 1)
+    }
+
+    fn from_state_increment(state: u64, increment: u64) -> Self {
+        let mut rng = Self { state: state.wrapping_add(increment), increment };
+        rng.step();
+        rng
+    }
+
+    fn step(&mut self) {
+        self.state = self.state
+            .wrapping_mul(MULTIPLIER)
+            .wrapping_add(self.increment);
+    }
+}
+
+/// The lowest bit of the stream (`seed[8]`) is ignored, since the increment has to be odd
+impl SeedableRng for GameRng {
+    type Seed = [u8; 16];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let mut seed_u64 = [0u64; 2];
+        le::read_u64_into(&seed, &mut seed_u64);
+
+        Self::from_state_increment(seed_u64[0], seed_u64[1] | 1)
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        const ROTATE: u32 = 59; // 64 - 5
+        const XSHIFT: u32 = 18; // (5 + 32) / 2
+        const SPARE: u32 = 27; // 64 - 32 - 5
+
+        let state = self.state;
+        self.step();
+
+        // xorshift high, followed by a random rotation
+        let rotation = (state >> ROTATE) as u32;
+        let xorshifted = (((state >> XSHIFT) ^ state) >> SPARE) as u32;
+        xorshifted.rotate_right(rotation)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_reference_implementation() {
+        // the output of the demo of the PCG reference implementation
+        const EXPECTED: [u32; 6] = [0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e];
+
+        let mut rng = GameRng::new(42, 54);
+        for &expected in &EXPECTED {
+            assert_eq!(rng.next_u32(), expected);
+        }
+    }
+}
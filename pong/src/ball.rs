@@ -0,0 +1,141 @@
+use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{GameObj, GameRng, PlayField, Player, Position, Step, StepResult, Unit};
+
+#[derive(Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Ball {
+    position: Position,
+    velocity: Velocity,
+}
+
+/// The distance the ball travels in one step, in pixels
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Velocity {
+    pub dx: isize,
+    pub dy: isize,
+}
+
+impl Ball {
+    /// The steepest vertical speed, a ball can be served with
+    pub const MAX_SERVE_DY: isize = 2;
+
+    /// A ball in the center of the field, that moves towards `player`
+    pub(crate) fn serve(player: Player, speed: isize, rng: &mut GameRng) -> Self {
+        let dx = match player {
+            Player::Left => -speed,
+            Player::Right => speed,
+        };
+
+        Self {
+            position: Position {
+                x: (PlayField::WIDTH - Self::WIDTH) / 2,
+                y: (PlayField::HEIGHT - Self::HEIGHT) / 2,
+            },
+            velocity: Velocity {
+                dx,
+                // isize is sampled differently on 32 and 64 bit targets, which would make the
+                // serve depend on the platform
+                dy: rng.gen_range(-(Self::MAX_SERVE_DY as i32)..=Self::MAX_SERVE_DY as i32) as isize,
+            },
+        }
+    }
+
+    pub fn velocity(&self) -> Velocity {
+        self.velocity
+    }
+
+    /// Sends the ball back, after it hit a paddle at its new x coordinate `x`
+    pub(crate) fn bounce(&mut self, x: Unit, velocity: Velocity) {
+        self.position.x = x;
+        self.velocity = velocity;
+    }
+}
+
+impl GameObj for Ball {
+    const WIDTH: usize = 4;
+    const HEIGHT: usize = 4;
+
+    fn position(&self) -> Position {
+        self.position
+    }
+}
+
+/// The ball bounces off the top and the bottom of the field, and leaves it on the left and the
+/// right
+impl Step for Ball {
+    fn step(&mut self, _rng: &mut GameRng) -> StepResult {
+        const MAX_X: isize = (PlayField::WIDTH - Ball::WIDTH) as isize;
+        const MAX_Y: isize = (PlayField::HEIGHT - Ball::HEIGHT) as isize;
+
+        let x = self.position.x as isize + self.velocity.dx;
+        let mut y = self.position.y as isize + self.velocity.dy;
+        if y < 0 {
+            y = -y;
+            self.velocity.dy = -self.velocity.dy;
+        } else if y > MAX_Y {
+            y = 2 * MAX_Y - y;
+            self.velocity.dy = -self.velocity.dy;
+        }
+
+        self.position = Position {
+            x: x.clamp(0, MAX_X) as Unit,
+            y: y as Unit,
+        };
+
+        StepResult {
+            survived: (0..=MAX_X).contains(&x),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    const MAX_Y: Unit = PlayField::HEIGHT - Ball::HEIGHT;
+
+    fn step(ball: &mut Ball) -> bool {
+        ball.step(&mut GameRng::seed_from_u64(0)).survived
+    }
+
+    #[test]
+    fn bounces_off_the_top() {
+        let mut ball = Ball {
+            position: Position { x: 100, y: 1 },
+            velocity: Velocity { dx: 2, dy: -3 },
+        };
+
+        assert!(step(&mut ball));
+        assert_eq!(ball.position(), Position { x: 102, y: 2 });
+        assert_eq!(ball.velocity(), Velocity { dx: 2, dy: 3 });
+    }
+
+    #[test]
+    fn bounces_off_the_bottom() {
+        let mut ball = Ball {
+            position: Position { x: 100, y: MAX_Y - 1 },
+            velocity: Velocity { dx: -2, dy: 3 },
+        };
+
+        assert!(step(&mut ball));
+        assert_eq!(ball.position(), Position { x: 98, y: MAX_Y - 2 });
+        assert_eq!(ball.velocity(), Velocity { dx: -2, dy: -3 });
+    }
+
+    #[test]
+    fn leaves_on_the_sides() {
+        let mut ball = Ball {
+            position: Position { x: 1, y: 50 },
+            velocity: Velocity { dx: -2, dy: 0 },
+        };
+
+        assert!(!step(&mut ball));
+        assert_eq!(ball.position().x, 0);
+    }
+}
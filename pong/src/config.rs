@@ -0,0 +1,73 @@
+use core::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::Score;
+use crate::paddle::Paddle;
+
+/// The rules of a game
+///
+/// When deserialized, every missing field falls back to its [default](GameConfig::default) value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GameConfig {
+    /// The number of points, that wins the game
+    pub points_to_win: Score,
+    /// How far a paddle moves in one step
+    pub paddle_speed: usize,
+    /// The horizontal speed of a served ball
+    pub ball_speed: isize,
+    /// The horizontal speed, after which paddle hits don't speed up the ball anymore
+    pub max_ball_speed: isize,
+    /// The number of steps between a point and the next serve
+    pub serve_ticks: usize,
+}
+
+impl GameConfig {
+    /// Checks, that the config describes a playable game
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.points_to_win <= 0 {
+            return Err(ConfigError::Invalid("at least one point has to be needed to win"));
+        }
+        if self.paddle_speed == 0 {
+            return Err(ConfigError::Invalid("the paddles have to be able to move"));
+        }
+        if self.ball_speed <= 0 || self.ball_speed > self.max_ball_speed {
+            return Err(ConfigError::Invalid("the ball speed has to be between 1 and the max ball speed"));
+        }
+        // otherwise, the ball could jump over a paddle and out of the field in one step
+        if self.max_ball_speed > Paddle::MARGIN as isize {
+            return Err(ConfigError::Invalid("the ball is faster than the gap between paddle and edge"));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            points_to_win: 11,
+            paddle_speed: 3,
+            ball_speed: 2,
+            max_ball_speed: 6,
+            serve_ticks: 30,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The config describes a game, that can't be played
+    Invalid(&'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(reason) => write!(f, "invalid game config: {}", reason),
+        }
+    }
+}
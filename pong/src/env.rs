@@ -0,0 +1,91 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use game::{ActionSpace, Environment, Reward};
+
+use crate::{GameObj, GameStatus, Instruction, PlayField, Player, Position, Score};
+use crate::ball::{Ball, Velocity};
+use crate::paddle::Paddle;
+
+/// Everything an agent can see of a [`PlayField`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Observation {
+    pub ball: Position,
+    pub ball_velocity: Velocity,
+    /// The paddles, indexed by [`Player::index`]
+    pub paddles: [Position; 2],
+    /// The scores, indexed by [`Player::index`]
+    pub scores: [Score; 2],
+    /// The number of steps until the ball is served
+    pub serve_ticks_left: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Info {
+    pub status: GameStatus,
+    pub scores: [Score; 2],
+}
+
+impl PlayField {
+    pub fn observe(&self) -> Observation {
+        Observation {
+            ball: self.ball.position(),
+            ball_velocity: self.ball.velocity(),
+            paddles: [self.paddles[0].position(), self.paddles[1].position()],
+            scores: self.scores,
+            serve_ticks_left: self.serve_ticks_left,
+        }
+    }
+
+    /// The instruction, that moves the paddle of `player` towards the ball
+    ///
+    /// This is the opponent of the agent in the [`Environment`].
+    pub fn follow_ball(&self, player: Player) -> Instruction {
+        let ball = (self.ball.position().y + Ball::HEIGHT / 2) as isize;
+        let paddle = (self.paddle(player).position().y + Paddle::HEIGHT / 2) as isize;
+
+        match ball - paddle {
+            offset if offset < -(Paddle::HEIGHT as isize) / 4 => Instruction::MoveUp,
+            offset if offset > Paddle::HEIGHT as isize / 4 => Instruction::MoveDown,
+            _ => Instruction::None,
+        }
+    }
+}
+
+/// The agent plays the left paddle against an opponent, that [follows the ball](PlayField::follow_ball)
+///
+/// The reward is `1` for every point of the agent, and `-1` for every point of the opponent.
+impl Environment for PlayField {
+    type Observation = Observation;
+    type Action = Instruction;
+    type Info = Info;
+
+    fn reset(&mut self, seed: u64) -> Self::Observation {
+        *self = PlayField::with_seed_and_config(seed, self.config);
+        self.observe()
+    }
+
+    fn step(&mut self, action: Self::Action) -> (Self::Observation, Reward, bool, Self::Info) {
+        let opponent = self.follow_ball(Player::Right);
+        let status = PlayField::step(self, [action, opponent]);
+        let reward = match status {
+            GameStatus::Scored { player } | GameStatus::Won { player } => match player {
+                Player::Left => 1.,
+                Player::Right => -1.,
+            },
+            GameStatus::Running => 0.,
+        };
+        let info = Info {
+            status,
+            scores: self.scores,
+        };
+
+        (self.observe(), reward, status.is_done(), info)
+    }
+
+    fn action_space(&self) -> ActionSpace {
+        ActionSpace::Discrete(Instruction::COUNT)
+    }
+}
@@ -0,0 +1,376 @@
+#![no_std]
+
+use game::{Canvas, Color, Game};
+pub use game::{GameObj, GameRng, Position, Score, Step, StepResult, Unit};
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::ball::Ball;
+use crate::config::GameConfig;
+use crate::env::Observation;
+use crate::paddle::Paddle;
+
+pub mod ball;
+pub mod config;
+pub mod env;
+pub mod paddle;
+
+/// One of the two players, each controlling one paddle
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Player {
+    Left,
+    Right,
+}
+
+impl Player {
+    pub const BOTH: [Player; 2] = [Player::Left, Player::Right];
+
+    /// `0` for [`Player::Left`], `1` for [`Player::Right`]
+    pub fn index(&self) -> usize {
+        match self {
+            Self::Left => 0,
+            Self::Right => 1,
+        }
+    }
+
+    pub fn opponent(&self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Instruction {
+    MoveUp,
+    MoveDown,
+    None,
+}
+
+impl Instruction {
+    /// The number of distinct instructions
+    pub const COUNT: usize = 3;
+
+    /// Maps `0..Instruction::COUNT` to all instructions
+    pub fn from_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(Self::None),
+            1 => Some(Self::MoveUp),
+            2 => Some(Self::MoveDown),
+            _ => None,
+        }
+    }
+
+    /// The inverse of [`Instruction::from_index`]
+    pub fn index(&self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::MoveUp => 1,
+            Self::MoveDown => 2,
+        }
+    }
+}
+
+/// The outcome of a single [`PlayField::step`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameStatus {
+    Running,
+    /// The player scored a point, and the ball is served again after
+    /// [`GameConfig::serve_ticks`] steps
+    Scored { player: Player },
+    /// The player reached [`GameConfig::points_to_win`]
+    Won { player: Player },
+}
+
+impl GameStatus {
+    /// Whether the game is over
+    pub fn is_done(&self) -> bool {
+        matches!(self, Self::Won { .. })
+    }
+}
+
+impl game::Status for GameStatus {
+    fn is_done(&self) -> bool {
+        GameStatus::is_done(self)
+    }
+}
+
+/// A game of Pong between two players
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PlayField {
+    ball: Ball,
+    paddles: [Paddle; 2],
+
+    scores: [Score; 2],
+    status: GameStatus,
+    serve_ticks_left: usize,
+
+    config: GameConfig,
+    seed: u64,
+    rng: GameRng,
+}
+
+/// A game with the default rules and a random seed
+impl Default for PlayField {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlayField {
+    pub const WIDTH: Unit = 256;
+    pub const HEIGHT: Unit = 192;
+
+    /// Creates a game with the default rules and a random seed
+    pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    /// Creates a game with the default rules, whose randomness is fully determined by `seed`
+    ///
+    /// Two games with the same seed, that receive the same instructions, are identical.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_seed_and_config(seed, GameConfig::default())
+    }
+
+    /// Creates a game with the given rules, whose randomness is fully determined by `seed`
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the config is [invalid](GameConfig::validate).
+    pub fn with_seed_and_config(seed: u64, config: GameConfig) -> Self {
+        if let Err(err) = config.validate() {
+            panic!("{}", err);
+        }
+
+        let mut rng = GameRng::seed_from_u64(seed);
+        let serve_to = match rng.gen::<bool>() {
+            true => Player::Left,
+            false => Player::Right,
+        };
+
+        Self {
+            ball: Ball::serve(serve_to, config.ball_speed, &mut rng),
+            paddles: [Paddle::new(Player::Left), Paddle::new(Player::Right)],
+            scores: [0; 2],
+            status: GameStatus::Running,
+            serve_ticks_left: config.serve_ticks,
+            config,
+            seed,
+            rng,
+        }
+    }
+
+    pub fn ball(&self) -> &Ball {
+        &self.ball
+    }
+
+    pub fn paddle(&self, player: Player) -> &Paddle {
+        &self.paddles[player.index()]
+    }
+
+    pub fn score(&self, player: Player) -> Score {
+        self.scores[player.index()]
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The rules of the game
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    /// The number of steps until the ball is served
+    pub fn serve_ticks_left(&self) -> usize {
+        self.serve_ticks_left
+    }
+
+    /// The status returned by the last [`PlayField::step`]
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    /// Advances the game by one step, the left paddle with `instructions[0]` and the right paddle
+    /// with `instructions[1]`
+    ///
+    /// Once the returned status [is done](GameStatus::is_done), the game does not change anymore.
+    pub fn step(&mut self, instructions: [Instruction; 2]) -> GameStatus {
+        if self.status.is_done() {
+            return self.status;
+        }
+
+        for player in Player::BOTH {
+            let paddle = &mut self.paddles[player.index()];
+            match instructions[player.index()] {
+                Instruction::MoveUp => paddle.move_up(self.config.paddle_speed),
+                Instruction::MoveDown => paddle.move_down(self.config.paddle_speed),
+                Instruction::None => {}
+            }
+        }
+
+        // the paddles can move, while the ball waits to be served
+        if self.serve_ticks_left > 0 {
+            self.serve_ticks_left -= 1;
+            self.status = GameStatus::Running;
+            return self.status;
+        }
+
+        let previous = self.ball.position();
+        let survived = self.ball.step(&mut self.rng).survived;
+        for paddle in &self.paddles {
+            paddle.deflect(&mut self.ball, previous, self.config.max_ball_speed);
+        }
+
+        // the ball can't be deflected and leave the field in the same step, since it's never
+        // faster than the gap between the paddles and the edges of the field
+        self.status = match survived {
+            true => GameStatus::Running,
+            false => self.score_point(),
+        };
+
+        self.status
+    }
+
+    /// Awards a point for the ball, that left the field, and serves it again
+    fn score_point(&mut self) -> GameStatus {
+        // the player on the other side of the field, than the ball left, scores
+        let player = match self.ball.position().x < Self::WIDTH / 2 {
+            true => Player::Right,
+            false => Player::Left,
+        };
+
+        self.scores[player.index()] += 1;
+        if self.scores[player.index()] >= self.config.points_to_win {
+            return GameStatus::Won { player };
+        }
+
+        // the ball is served towards the player, that conceded the point
+        self.ball = Ball::serve(player.opponent(), self.config.ball_speed, &mut self.rng);
+        self.serve_ticks_left = self.config.serve_ticks;
+        GameStatus::Scored { player }
+    }
+}
+
+/// A head-to-head game, whose actions are the instructions of both players
+impl Game for PlayField {
+    type Observation = Observation;
+    type Action = [Instruction; 2];
+    type Status = GameStatus;
+
+    const WIDTH: usize = PlayField::WIDTH;
+    const HEIGHT: usize = PlayField::HEIGHT;
+
+    fn step(&mut self, action: Self::Action) -> Self::Status {
+        PlayField::step(self, action)
+    }
+
+    fn status(&self) -> Self::Status {
+        self.status
+    }
+
+    fn observe(&self) -> Self::Observation {
+        PlayField::observe(self)
+    }
+
+    fn players(&self) -> usize {
+        2
+    }
+
+    fn score(&self, player: usize) -> Score {
+        self.scores[player]
+    }
+
+    fn render(&self, canvas: &mut dyn Canvas) {
+        const DASH: Unit = 8;
+
+        // the dashed center line
+        for y in (0..Self::HEIGHT).step_by(DASH * 2) {
+            canvas.fill_rect(Self::WIDTH / 2 - 1, y, 2, DASH, Color::GREEN);
+        }
+        for paddle in &self.paddles {
+            let Position { x, y } = paddle.position();
+            canvas.fill_rect(x, y, Paddle::WIDTH, Paddle::HEIGHT, Color::WHITE);
+        }
+        if self.serve_ticks_left == 0 {
+            let Position { x, y } = self.ball.position();
+            canvas.fill_rect(x, y, Ball::WIDTH, Ball::HEIGHT, Color::WHITE);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ball::Velocity;
+
+    use super::*;
+
+    // a field, whose ball leaves it past the paddle of `player` in the next step
+    fn about_to_concede(player: Player) -> PlayField {
+        let mut play_field = PlayField::with_seed(0);
+        play_field.serve_ticks_left = 0;
+        match player {
+            Player::Left => play_field.ball.bounce(1, Velocity { dx: -2, dy: 0 }),
+            Player::Right => play_field.ball.bounce(PlayField::WIDTH - Ball::WIDTH - 1, Velocity { dx: 2, dy: 0 }),
+        }
+
+        play_field
+    }
+
+    #[test]
+    fn opponent_scores_and_ball_is_served_to_the_conceding_player() {
+        for player in Player::BOTH {
+            let mut play_field = about_to_concede(player);
+            let scorer = player.opponent();
+
+            assert_eq!(play_field.step([Instruction::None; 2]), GameStatus::Scored { player: scorer });
+            assert_eq!(play_field.score(scorer), 1);
+            assert_eq!(play_field.score(player), 0);
+            assert_eq!(play_field.serve_ticks_left(), play_field.config().serve_ticks);
+
+            let dx = play_field.ball().velocity().dx;
+            match player {
+                Player::Left => assert!(dx < 0),
+                Player::Right => assert!(dx > 0),
+            }
+        }
+    }
+
+    #[test]
+    fn ball_waits_to_be_served() {
+        let mut play_field = about_to_concede(Player::Left);
+        play_field.step([Instruction::None; 2]);
+        let served = play_field.ball().position();
+
+        for _ in 0..play_field.config().serve_ticks {
+            assert_eq!(play_field.step([Instruction::None; 2]), GameStatus::Running);
+            assert_eq!(play_field.ball().position(), served);
+        }
+        play_field.step([Instruction::None; 2]);
+        assert_ne!(play_field.ball().position(), served);
+    }
+
+    #[test]
+    fn reaching_the_points_to_win_ends_the_game() {
+        let mut play_field = about_to_concede(Player::Left);
+        play_field.scores[Player::Right.index()] = play_field.config().points_to_win - 1;
+
+        let won = GameStatus::Won { player: Player::Right };
+        assert_eq!(play_field.step([Instruction::None; 2]), won);
+        assert!(won.is_done());
+        assert_eq!(play_field.score(Player::Right), play_field.config().points_to_win);
+
+        // a finished game doesn't change anymore
+        let ball = play_field.ball().position();
+        assert_eq!(play_field.step([Instruction::MoveUp; 2]), won);
+        assert_eq!(play_field.ball().position(), ball);
+        assert_eq!(play_field.paddle(Player::Left).position(), Paddle::new(Player::Left).position());
+    }
+}
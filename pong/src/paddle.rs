@@ -0,0 +1,139 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{GameObj, PlayField, Player, Position, Unit};
+use crate::ball::{Ball, Velocity};
+
+#[derive(Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Paddle {
+    position: Position,
+    player: Player,
+}
+
+impl Paddle {
+    /// The gap between a paddle and its edge of the field
+    pub const MARGIN: Unit = 8;
+
+    pub fn new(player: Player) -> Self {
+        let x = match player {
+            Player::Left => Self::MARGIN,
+            Player::Right => PlayField::WIDTH - Self::MARGIN - Self::WIDTH,
+        };
+
+        Self {
+            position: Position {
+                x,
+                y: (PlayField::HEIGHT - Self::HEIGHT) / 2,
+            },
+            player,
+        }
+    }
+
+    pub(crate) fn move_up(&mut self, speed: Unit) {
+        self.position.y = self.position.y.saturating_sub(speed);
+    }
+
+    pub(crate) fn move_down(&mut self, speed: Unit) {
+        self.position.y = (self.position.y + speed).min(PlayField::HEIGHT - Self::HEIGHT);
+    }
+
+    /// Sends the ball back, if it crossed the front of the paddle since `previous`
+    ///
+    /// The further from the center of the paddle the ball hits, the steeper it bounces off.
+    /// Every hit makes the ball faster, up to `max_speed`.
+    pub(crate) fn deflect(&self, ball: &mut Ball, previous: Position, max_speed: isize) {
+        let Velocity { dx, .. } = ball.velocity();
+        // the x coordinate of the ball, when it touches the front of the paddle
+        let (front, crossed) = match self.player {
+            Player::Left => {
+                let front = self.position.x + Self::WIDTH;
+                (front, dx < 0 && previous.x >= front && ball.position().x < front)
+            }
+            Player::Right => {
+                let front = self.position.x - Ball::WIDTH;
+                (front, dx > 0 && previous.x <= front && ball.position().x > front)
+            }
+        };
+
+        let y = ball.position().y;
+        let on_height = y < self.position.y + Self::HEIGHT && self.position.y < y + Ball::HEIGHT;
+        if !crossed || !on_height { return; }
+
+        let offset = (ball.position().y + Ball::HEIGHT / 2) as isize
+            - (self.position.y + Self::HEIGHT / 2) as isize;
+        let speed = (dx.abs() + 1).min(max_speed);
+        ball.bounce(front, Velocity {
+            dx: if dx < 0 { speed } else { -speed },
+            dy: offset / 4,
+        });
+    }
+}
+
+impl GameObj for Paddle {
+    const WIDTH: usize = 4;
+    const HEIGHT: usize = 24;
+
+    fn position(&self) -> Position {
+        self.position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::{GameRng, Step};
+
+    use super::*;
+
+    const MAX_SPEED: isize = 6;
+
+    // a ball at the height of the center of the field, that crossed the front of the left paddle
+    // in its last step
+    fn deflect(paddle: &Paddle, dx: isize) -> Velocity {
+        let mut rng = GameRng::seed_from_u64(0);
+        let mut ball = Ball::serve(Player::Left, 2, &mut rng);
+        ball.bounce(Paddle::MARGIN + Paddle::WIDTH + 1, Velocity { dx, dy: 0 });
+
+        let previous = ball.position();
+        ball.step(&mut rng);
+        paddle.deflect(&mut ball, previous, MAX_SPEED);
+        ball.velocity()
+    }
+
+    fn moved(steps: isize) -> Paddle {
+        let mut paddle = Paddle::new(Player::Left);
+        for _ in 0..steps.abs() {
+            match steps < 0 {
+                true => paddle.move_up(3),
+                false => paddle.move_down(3),
+            }
+        }
+
+        paddle
+    }
+
+    #[test]
+    fn center_hit_bounces_straight_back() {
+        assert_eq!(deflect(&moved(0), -2), Velocity { dx: 3, dy: 0 });
+    }
+
+    #[test]
+    fn edge_hits_bounce_off_steeper() {
+        // the ball hits the top half of the paddle
+        assert_eq!(deflect(&moved(4), -2), Velocity { dx: 3, dy: -3 });
+        // the ball hits the bottom half of the paddle
+        assert_eq!(deflect(&moved(-4), -2), Velocity { dx: 3, dy: 3 });
+    }
+
+    #[test]
+    fn hits_speed_the_ball_up_to_the_max_speed() {
+        assert_eq!(deflect(&moved(0), -MAX_SPEED).dx, MAX_SPEED);
+    }
+
+    #[test]
+    fn missed_ball_passes() {
+        assert_eq!(deflect(&moved(20), -2), Velocity { dx: -2, dy: 0 });
+    }
+}
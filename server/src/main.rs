@@ -1,3 +1,44 @@
-fn main() {
-    println!("Hello, world!");
+use std::io;
+
+use actix_web::{App, HttpResponse, HttpServer, web};
+
+use crate::replays::{ReplayStore, StoreError};
+
+mod replays;
+
+const ADDRESS: &str = "127.0.0.1:8080";
+// next to the manifest, independent of the working directory
+const REPLAY_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/replays");
+
+/// Stores a replay, that was recorded by a client
+///
+/// Verifying a replay re-simulates the whole game, so it runs on the blocking thread pool instead
+/// of the worker.
+async fn upload_replay(store: web::Data<ReplayStore>, body: web::Bytes) -> HttpResponse {
+    match web::block(move || store.save(&body)).await {
+        Ok(Ok(name)) => HttpResponse::Created().body(name),
+        Ok(Err(err @ StoreError::Replay(_))) => HttpResponse::BadRequest().body(err.to_string()),
+        Ok(Err(err @ StoreError::Io(_))) => {
+            eprintln!("{}", err);
+            HttpResponse::InternalServerError().finish()
+        }
+        Err(err) => {
+            eprintln!("could not verify replay: {}", err);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[actix_web::main]
+async fn main() -> io::Result<()> {
+    let store = web::Data::new(ReplayStore::open(REPLAY_DIR)?);
+
+    HttpServer::new(move || {
+        App::new()
+            .app_data(store.clone())
+            .route("/api/replays", web::post().to(upload_replay))
+    })
+        .bind(ADDRESS)?
+        .run()
+        .await
 }
@@ -0,0 +1,66 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::PathBuf;
+
+use space_invaders::replay::{Replay, ReplayError};
+
+/// The longest replay the server verifies, about half an hour of play at 30 steps per second
+///
+/// This is far below [`replay::MAX_INPUTS`](space_invaders::replay::MAX_INPUTS), since every
+/// upload is re-simulated.
+pub const MAX_STEPS: usize = 30 * 60 * 30;
+
+/// A directory of verified replays
+///
+/// Replays are stored in the binary format of [`Replay::to_bytes`], named after a hash of their
+/// content, so uploading the same replay twice doesn't create a second file.
+pub struct ReplayStore {
+    dir: PathBuf,
+}
+
+impl ReplayStore {
+    /// Opens the store in `dir`, and creates the directory, if it doesn't exist
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self { dir })
+    }
+
+    /// Decodes and re-simulates the replay, and stores it, if it's valid
+    ///
+    /// Replays longer than [`MAX_STEPS`] are rejected. Returns the name of the replay in the store.
+    pub fn save(&self, bytes: &[u8]) -> Result<String, StoreError> {
+        let replay = Replay::from_bytes(bytes).map_err(StoreError::Replay)?;
+        if replay.inputs().len() > MAX_STEPS {
+            return Err(StoreError::Replay(ReplayError::TooLong));
+        }
+        replay.verify().map_err(StoreError::Replay)?;
+
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        let name = format!("{:016x}.sirp", hasher.finish());
+        fs::write(self.dir.join(&name), bytes).map_err(StoreError::Io)?;
+
+        Ok(name)
+    }
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    /// The uploaded bytes are not a valid replay
+    Replay(ReplayError),
+    Io(io::Error),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Replay(err) => write!(f, "{}", err),
+            Self::Io(err) => write!(f, "could not store replay: {}", err),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use core::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::PlayField;
+
+/// The fewest snakes, a game can be played with
+pub const MIN_SNAKES: usize = 2;
+/// The most snakes, a game can be played with
+pub const MAX_SNAKES: usize = 8;
+
+/// The rules of a game
+///
+/// When deserialized, every missing field falls back to its [default](GameConfig::default) value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GameConfig {
+    /// The number of snakes, one per player
+    pub snakes: usize,
+    /// The length of the snakes at the start of the game
+    pub initial_length: usize,
+    /// The amount of food on the grid at once
+    pub food: usize,
+    /// The number of steps, after which the longest snake wins
+    pub tick_limit: Option<usize>,
+}
+
+impl GameConfig {
+    /// Checks, that the config describes a playable game
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(MIN_SNAKES..=MAX_SNAKES).contains(&self.snakes) {
+            return Err(ConfigError::Invalid("there have to be between 2 and 8 snakes"));
+        }
+        // the snakes start in the middle of the grid
+        if self.initial_length == 0 || self.initial_length > PlayField::HEIGHT / 2 {
+            return Err(ConfigError::Invalid("the initial length has to be between 1 and half the grid height"));
+        }
+        if self.food == 0 {
+            return Err(ConfigError::Invalid("there has to be food"));
+        }
+        if self.tick_limit == Some(0) {
+            return Err(ConfigError::Invalid("the tick limit has to allow at least one step"));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            snakes: 2,
+            initial_length: 3,
+            food: 3,
+            tick_limit: Some(2000),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The config describes a game, that can't be played
+    Invalid(&'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(reason) => write!(f, "invalid game config: {}", reason),
+        }
+    }
+}
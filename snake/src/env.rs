@@ -0,0 +1,113 @@
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use game::{ActionSpace, Environment, Reward};
+
+use crate::{GameStatus, Instruction, PlayField, Position, Score};
+use crate::snake::Snake;
+
+/// Everything an agent can see of a [`PlayField`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Observation {
+    /// The snakes, indexed by player
+    pub snakes: Vec<Snake>,
+    pub food: Vec<Position>,
+    pub tick: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Info {
+    pub status: GameStatus,
+    /// The scores, indexed by player
+    pub scores: Vec<Score>,
+}
+
+impl PlayField {
+    pub fn observe(&self) -> Observation {
+        Observation {
+            snakes: self.snakes.clone(),
+            food: self.food.clone(),
+            tick: self.tick,
+        }
+    }
+
+    /// The instruction, that moves the snake of `player` towards the closest food, without running
+    /// into a snake or out of the grid in the next step
+    ///
+    /// This is the opponent of the agent in the [`Environment`].
+    pub fn seek_food(&self, player: usize) -> Instruction {
+        let snake = &self.snakes[player];
+        let is_free = |cell: Position| {
+            self.snakes
+                .iter()
+                .filter(|snake| snake.is_alive())
+                .all(|snake| snake.body().take(snake.len() - 1).all(|body| body != cell))
+        };
+        let distance_to_food = |cell: Position| {
+            self.food
+                .iter()
+                .map(|&food| cell.distance(food))
+                .min()
+                .unwrap_or(0)
+        };
+
+        Instruction::ALL
+            .iter()
+            .filter_map(|&instruction| {
+                let cell = snake.direction().turn(instruction).neighbour_of(snake.head())?;
+                match is_free(cell) {
+                    true => Some((distance_to_food(cell), instruction)),
+                    false => None,
+                }
+            })
+            .min_by_key(|&(distance, _)| distance)
+            .map_or(Instruction::None, |(_, instruction)| instruction)
+    }
+}
+
+/// The agent plays the first snake against opponents, that [seek food](PlayField::seek_food)
+///
+/// The reward is `1` for every food the agent eats, and `-1` when it dies. The episode ends with
+/// the death of the agent, or with the end of the game.
+impl Environment for PlayField {
+    type Observation = Observation;
+    type Action = Instruction;
+    type Info = Info;
+
+    fn reset(&mut self, seed: u64) -> Self::Observation {
+        *self = PlayField::with_seed_and_config(seed, self.config);
+        self.observe()
+    }
+
+    fn step(&mut self, action: Self::Action) -> (Self::Observation, Reward, bool, Self::Info) {
+        let instructions: Vec<Instruction> = (0..self.snakes.len())
+            .map(|player| match player {
+                0 => action,
+                _ => self.seek_food(player),
+            })
+            .collect();
+
+        let score = self.snakes[0].score();
+        let status = PlayField::step(self, &instructions);
+        let agent = &self.snakes[0];
+
+        let reward = match agent.is_alive() {
+            true => (agent.score() - score) as Reward,
+            false => -1.,
+        };
+        let info = Info {
+            status,
+            scores: self.snakes.iter().map(Snake::score).collect(),
+        };
+
+        (self.observe(), reward, status.is_done() || !agent.is_alive(), info)
+    }
+
+    fn action_space(&self) -> ActionSpace {
+        ActionSpace::Discrete(Instruction::COUNT)
+    }
+}
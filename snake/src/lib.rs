@@ -0,0 +1,534 @@
+//! A multi-agent game of Snake
+//!
+//! Between two and eight snakes move on the same grid at the same time. A snake dies, when it
+//! leaves the grid or runs into a snake, including itself. The last snake alive wins.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use game::{Canvas, Color, Game};
+pub use game::{GameRng, Position, Score, Unit};
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+use crate::env::Observation;
+use crate::snake::Snake;
+
+pub mod config;
+pub mod env;
+pub mod snake;
+
+/// The direction, a snake is heading in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// The neighbouring cell of `cell` in this direction, or `None` if it's outside of the grid
+    pub fn neighbour_of(&self, cell: Position) -> Option<Position> {
+        let Position { x, y } = cell;
+        let (x, y) = match self {
+            Self::Up => (x, y.checked_sub(1)?),
+            Self::Right => (x + 1, y),
+            Self::Down => (x, y + 1),
+            Self::Left => (x.checked_sub(1)?, y),
+        };
+
+        match x < PlayField::WIDTH && y < PlayField::HEIGHT {
+            true => Some(Position { x, y }),
+            false => None,
+        }
+    }
+
+    pub fn turn(&self, instruction: Instruction) -> Self {
+        match (instruction, self) {
+            (Instruction::None, _) => *self,
+            (Instruction::TurnLeft, Self::Up) | (Instruction::TurnRight, Self::Down) => Self::Left,
+            (Instruction::TurnLeft, Self::Right) | (Instruction::TurnRight, Self::Left) => Self::Up,
+            (Instruction::TurnLeft, Self::Down) | (Instruction::TurnRight, Self::Up) => Self::Right,
+            (Instruction::TurnLeft, Self::Left) | (Instruction::TurnRight, Self::Right) => Self::Down,
+        }
+    }
+}
+
+/// What a snake does in one step
+///
+/// The turns are relative to the current direction of the snake, so a snake can never turn back
+/// into itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Instruction {
+    TurnLeft,
+    TurnRight,
+    None,
+}
+
+impl Instruction {
+    /// The number of distinct instructions
+    pub const COUNT: usize = 3;
+    pub const ALL: [Instruction; Self::COUNT] = [Instruction::None, Instruction::TurnLeft, Instruction::TurnRight];
+
+    /// Maps `0..Instruction::COUNT` to all instructions
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    /// The inverse of [`Instruction::from_index`]
+    pub fn index(&self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::TurnLeft => 1,
+            Self::TurnRight => 2,
+        }
+    }
+}
+
+/// The outcome of a single [`PlayField::step`]
+///
+/// Players are identified by the index of their snake.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameStatus {
+    Running,
+    /// The snake of the player is the last one alive, or the longest one at the tick limit
+    Won { player: usize },
+    /// The last snakes died in the same step, or there is no single longest snake at the tick limit
+    Draw,
+}
+
+impl GameStatus {
+    /// Whether the game is over
+    pub fn is_done(&self) -> bool {
+        !matches!(self, Self::Running)
+    }
+}
+
+impl game::Status for GameStatus {
+    fn is_done(&self) -> bool {
+        GameStatus::is_done(self)
+    }
+}
+
+/// A game of Snake between [`GameConfig::snakes`] players
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PlayField {
+    snakes: Vec<Snake>,
+    food: Vec<Position>,
+
+    status: GameStatus,
+    tick: usize,
+
+    config: GameConfig,
+    seed: u64,
+    rng: GameRng,
+}
+
+/// A game with the default rules and a random seed
+impl Default for PlayField {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlayField {
+    /// The width of the grid, in cells
+    pub const WIDTH: Unit = 32;
+    /// The height of the grid, in cells
+    pub const HEIGHT: Unit = 32;
+    /// The size of a cell, when the game is rendered
+    pub const CELL_SIZE: usize = 8;
+
+    /// Creates a game with the default rules and a random seed
+    pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    /// Creates a game with the default rules, whose randomness is fully determined by `seed`
+    ///
+    /// Two games with the same seed, that receive the same instructions, are identical.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_seed_and_config(seed, GameConfig::default())
+    }
+
+    /// Creates a game with the given rules, whose randomness is fully determined by `seed`
+    ///
+    /// The snakes start in evenly spaced columns, heading up, so no player has an advantage.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the config is [invalid](GameConfig::validate).
+    pub fn with_seed_and_config(seed: u64, config: GameConfig) -> Self {
+        if let Err(err) = config.validate() {
+            panic!("{}", err);
+        }
+
+        let snakes = (0..config.snakes)
+            .map(|i| {
+                let head = Position {
+                    x: (i + 1) * Self::WIDTH / (config.snakes + 1),
+                    y: (Self::HEIGHT - config.initial_length) / 2,
+                };
+                Snake::spawn(head, Direction::Up, config.initial_length)
+            })
+            .collect();
+
+        let mut play_field = Self {
+            snakes,
+            food: Vec::with_capacity(config.food),
+            status: GameStatus::Running,
+            tick: 0,
+            config,
+            seed,
+            rng: GameRng::seed_from_u64(seed),
+        };
+        play_field.spawn_food();
+
+        play_field
+    }
+
+    /// The snakes, indexed by player
+    pub fn snakes(&self) -> &[Snake] {
+        &self.snakes
+    }
+
+    pub fn snake(&self, player: usize) -> &Snake {
+        &self.snakes[player]
+    }
+
+    pub fn food(&self) -> &[Position] {
+        &self.food
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The rules of the game
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    /// The number of steps played so far
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    /// The status returned by the last [`PlayField::step`]
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    /// Advances the game by one step, the snake of player `i` with `instructions[i]`
+    ///
+    /// All snakes move at the same time, so every collision is decided on the positions before the
+    /// step. The tails of all snakes, that don't grow, move out of the way, even the ones of snakes,
+    /// that die in this step. Snakes, that move into the same cell, die both.
+    ///
+    /// Once the returned status [is done](GameStatus::is_done), the game does not change anymore.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if there is not exactly one instruction per snake.
+    pub fn step(&mut self, instructions: &[Instruction]) -> GameStatus {
+        assert_eq!(instructions.len(), self.snakes.len(), "there has to be one instruction per snake");
+        if self.status.is_done() {
+            return self.status;
+        }
+
+        self.tick += 1;
+        self.snakes
+            .iter_mut()
+            .zip(instructions)
+            .filter(|(snake, _)| snake.is_alive())
+            .for_each(|(snake, &instruction)| snake.turn(instruction));
+
+        // `None` for dead snakes, and for snakes, that leave the grid
+        let heads: Vec<Option<Position>> = self.snakes
+            .iter()
+            .map(|snake| match snake.is_alive() {
+                true => snake.next_head(),
+                false => None,
+            })
+            .collect();
+        let grows: Vec<bool> = heads
+            .iter()
+            .map(|head| matches!(head, Some(head) if self.food.contains(head)))
+            .collect();
+        let occupied = self.occupied_cells(&grows);
+
+        let collides = |head: Position| {
+            occupied[cell_index(head)] || heads.iter().filter(|&&other| other == Some(head)).count() > 1
+        };
+
+        for (player, &head) in heads.iter().enumerate() {
+            if !self.snakes[player].is_alive() {
+                continue;
+            }
+
+            let head = match head {
+                Some(head) if !collides(head) => head,
+                _ => {
+                    self.snakes[player].die(self.tick);
+                    continue;
+                }
+            };
+
+            self.snakes[player].advance(head, grows[player]);
+            if grows[player] {
+                self.food.retain(|&food| food != head);
+            }
+        }
+
+        self.spawn_food();
+        self.status = self.decide();
+        self.status
+    }
+
+    /// The cells, that the snakes occupy after their tails moved, indexed by `cell_index`
+    fn occupied_cells(&self, grows: &[bool]) -> [bool; Self::WIDTH * Self::HEIGHT] {
+        let mut occupied = [false; Self::WIDTH * Self::HEIGHT];
+
+        for (snake, &grows) in self.snakes.iter().zip(grows) {
+            if !snake.is_alive() {
+                continue;
+            }
+
+            let len = match grows {
+                true => snake.len(),
+                false => snake.len() - 1,
+            };
+            snake
+                .body()
+                .take(len)
+                .for_each(|cell| occupied[cell_index(cell)] = true);
+        }
+
+        occupied
+    }
+
+    /// Places new food on random free cells, until there is [`GameConfig::food`] food on the grid
+    fn spawn_food(&mut self) {
+        let mut occupied = self.occupied_cells(&[true; config::MAX_SNAKES][..self.snakes.len()]);
+        self.food
+            .iter()
+            .for_each(|&food| occupied[cell_index(food)] = true);
+
+        while self.food.len() < self.config.food {
+            let free = occupied.iter().filter(|&&occupied| !occupied).count();
+            // the grid is full
+            if free == 0 {
+                return;
+            }
+
+            // usize is sampled differently on 32 and 64 bit targets, which would make the food
+            // depend on the platform
+            let n = self.rng.gen_range(0..free as u32) as usize;
+            let index = occupied
+                .iter()
+                .enumerate()
+                .filter(|(_, &occupied)| !occupied)
+                .nth(n)
+                .map(|(index, _)| index)
+                .unwrap();
+
+            occupied[index] = true;
+            self.food.push(Position {
+                x: index % Self::WIDTH,
+                y: index / Self::WIDTH,
+            });
+        }
+    }
+
+    fn decide(&self) -> GameStatus {
+        let mut alive = self.snakes
+            .iter()
+            .enumerate()
+            .filter(|(_, snake)| snake.is_alive());
+
+        match (alive.next(), alive.next()) {
+            (None, _) => return GameStatus::Draw,
+            (Some((player, _)), None) => return GameStatus::Won { player },
+            _ => {}
+        }
+
+        match self.config.tick_limit {
+            Some(tick_limit) if self.tick >= tick_limit => self.longest_snake(),
+            _ => GameStatus::Running,
+        }
+    }
+
+    fn longest_snake(&self) -> GameStatus {
+        let len = |snake: &Snake| match snake.is_alive() {
+            true => snake.len(),
+            false => 0,
+        };
+        let longest = self.snakes
+            .iter()
+            .map(len)
+            .max()
+            .unwrap_or(0);
+
+        let mut longest_snakes = self.snakes
+            .iter()
+            .enumerate()
+            .filter(|(_, snake)| len(snake) == longest);
+
+        match (longest_snakes.next(), longest_snakes.next()) {
+            (Some((player, _)), None) => GameStatus::Won { player },
+            _ => GameStatus::Draw,
+        }
+    }
+}
+
+/// A game for all snakes, whose actions are the instructions of all players
+impl Game for PlayField {
+    type Observation = Observation;
+    type Action = Vec<Instruction>;
+    type Status = GameStatus;
+
+    const WIDTH: usize = PlayField::WIDTH * PlayField::CELL_SIZE;
+    const HEIGHT: usize = PlayField::HEIGHT * PlayField::CELL_SIZE;
+
+    fn step(&mut self, action: Self::Action) -> Self::Status {
+        PlayField::step(self, &action)
+    }
+
+    fn status(&self) -> Self::Status {
+        self.status
+    }
+
+    fn observe(&self) -> Self::Observation {
+        PlayField::observe(self)
+    }
+
+    fn players(&self) -> usize {
+        self.snakes.len()
+    }
+
+    fn score(&self, player: usize) -> Score {
+        self.snakes[player].score()
+    }
+
+    fn render(&self, canvas: &mut dyn Canvas) {
+        const CELL: usize = PlayField::CELL_SIZE;
+
+        for food in &self.food {
+            canvas.fill_rect(food.x * CELL + 1, food.y * CELL + 1, CELL - 2, CELL - 2, Color::RED);
+        }
+        for (snake, &color) in self.snakes.iter().zip(&Snake::COLORS) {
+            if !snake.is_alive() {
+                continue;
+            }
+            for cell in snake.body() {
+                canvas.fill_rect(cell.x * CELL, cell.y * CELL, CELL, CELL, color);
+            }
+        }
+    }
+}
+
+// the index of `cell` in a row-major buffer of the grid
+fn cell_index(cell: Position) -> usize {
+    cell.y * PlayField::WIDTH + cell.x
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    // food in the corner, that none of the snakes reaches
+    const AWAY: Position = Position { x: PlayField::WIDTH - 1, y: PlayField::HEIGHT - 1 };
+
+    fn arena(snakes: Vec<Snake>, food: Vec<Position>) -> PlayField {
+        let config = GameConfig {
+            snakes: snakes.len(),
+            food: food.len(),
+            ..GameConfig::default()
+        };
+        let mut play_field = PlayField::with_seed_and_config(0, config);
+        play_field.snakes = snakes;
+        play_field.food = food;
+
+        play_field
+    }
+
+    fn snake(x: Unit, y: Unit, direction: Direction) -> Snake {
+        Snake::spawn(Position { x, y }, direction, 3)
+    }
+
+    fn idle(play_field: &mut PlayField) -> GameStatus {
+        let instructions = vec![Instruction::None; play_field.snakes().len()];
+        play_field.step(&instructions)
+    }
+
+    #[test]
+    fn head_on_collision_kills_both() {
+        let mut play_field = arena(vec![snake(10, 10, Direction::Right), snake(12, 10, Direction::Left)], vec![AWAY]);
+
+        assert_eq!(idle(&mut play_field), GameStatus::Draw);
+        assert!(play_field.snakes().iter().all(|snake| snake.died_at() == Some(1)));
+    }
+
+    #[test]
+    fn swapping_heads_kills_both() {
+        let mut play_field = arena(vec![snake(10, 10, Direction::Right), snake(11, 10, Direction::Left)], vec![AWAY]);
+
+        assert_eq!(idle(&mut play_field), GameStatus::Draw);
+        assert!(play_field.snakes().iter().all(|snake| !snake.is_alive()));
+    }
+
+    #[test]
+    fn snakes_reaching_the_same_food_both_die() {
+        let food = Position { x: 11, y: 10 };
+        let mut play_field = arena(vec![snake(10, 10, Direction::Right), snake(12, 10, Direction::Left)], vec![food, AWAY]);
+
+        assert_eq!(idle(&mut play_field), GameStatus::Draw);
+        assert!(play_field.food().contains(&food));
+        assert!(play_field.snakes().iter().all(|snake| snake.score() == 0 && snake.len() == 3));
+    }
+
+    #[test]
+    fn single_snake_eats_the_food() {
+        let food = Position { x: 11, y: 10 };
+        let mut play_field = arena(vec![snake(10, 10, Direction::Right), snake(20, 20, Direction::Up)], vec![food]);
+
+        assert_eq!(idle(&mut play_field), GameStatus::Running);
+        assert_eq!(play_field.snake(0).head(), food);
+        assert_eq!(play_field.snake(0).score(), 1);
+        assert_eq!(play_field.snake(0).len(), 4);
+        assert!(!play_field.food().contains(&food));
+    }
+
+    #[test]
+    fn last_snake_alive_wins() {
+        let mut play_field = arena(
+            vec![snake(0, 5, Direction::Left), snake(10, 20, Direction::Up), snake(20, 20, Direction::Up)],
+            vec![AWAY],
+        );
+        assert_eq!(idle(&mut play_field), GameStatus::Running);
+        assert!(!play_field.snake(0).is_alive());
+
+        // snake 1 runs into the left edge of the grid
+        play_field.step(&[Instruction::None, Instruction::TurnLeft, Instruction::None]);
+        let mut status = GameStatus::Running;
+        for _ in 0..PlayField::WIDTH {
+            status = idle(&mut play_field);
+            if status.is_done() { break; }
+        }
+
+        assert_eq!(status, GameStatus::Won { player: 2 });
+        assert!(play_field.snake(2).is_alive());
+    }
+}
@@ -0,0 +1,106 @@
+use alloc::collections::VecDeque;
+
+use game::Color;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Direction, Instruction, Position, Score};
+
+/// The snake of one player
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Snake {
+    // the head is the first cell
+    body: VecDeque<Position>,
+    direction: Direction,
+    score: Score,
+    died_at: Option<usize>,
+}
+
+impl Snake {
+    /// The colors of the snakes, indexed by player
+    pub const COLORS: [Color; crate::config::MAX_SNAKES] = [
+        Color::GREEN,
+        Color { r: 0x3D, g: 0x8B, b: 0xFD },
+        Color { r: 0xFF, g: 0xC1, b: 0x07 },
+        Color { r: 0xD6, g: 0x33, b: 0x84 },
+        Color { r: 0x20, g: 0xC9, b: 0x97 },
+        Color { r: 0x6F, g: 0x42, b: 0xC1 },
+        Color { r: 0xFD, g: 0x7E, b: 0x14 },
+        Color::WHITE,
+    ];
+
+    /// A snake of length `len`, whose body extends from `head` in the opposite of `direction`
+    pub(crate) fn spawn(head: Position, direction: Direction, len: usize) -> Self {
+        let backwards = direction
+            .turn(Instruction::TurnLeft)
+            .turn(Instruction::TurnLeft);
+        let body = core::iter::successors(Some(head), |&cell| backwards.neighbour_of(cell))
+            .take(len)
+            .collect();
+
+        Self {
+            body,
+            direction,
+            score: 0,
+            died_at: None,
+        }
+    }
+
+    pub fn head(&self) -> Position {
+        self.body[0]
+    }
+
+    /// The cells of the snake, starting at the head
+    pub fn body(&self) -> impl Iterator<Item = Position> + '_ {
+        self.body.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.body.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.body.is_empty()
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// The amount of food, the snake ate
+    pub fn score(&self) -> Score {
+        self.score
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.died_at.is_none()
+    }
+
+    /// The tick, in which the snake died
+    pub fn died_at(&self) -> Option<usize> {
+        self.died_at
+    }
+
+    /// The cell, the head moves to in the next step, or `None` if the snake leaves the grid
+    pub fn next_head(&self) -> Option<Position> {
+        self.direction.neighbour_of(self.head())
+    }
+
+    pub(crate) fn turn(&mut self, instruction: Instruction) {
+        self.direction = self.direction.turn(instruction);
+    }
+
+    /// Moves the head to `head`, and eats the food there, if the snake `grows`
+    pub(crate) fn advance(&mut self, head: Position, grows: bool) {
+        self.body.push_front(head);
+        match grows {
+            true => self.score += 1,
+            false => { self.body.pop_back(); }
+        }
+    }
+
+    pub(crate) fn die(&mut self, tick: usize) {
+        self.died_at = Some(tick);
+    }
+}
@@ -2,85 +2,245 @@ use alloc::vec::Vec;
 use core::slice::Iter;
 
 use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-use crate::{Bullet, GameObj, GetHit, HitResult, PlayField, Position, Step, StepResult, Unit, WouldHit};
-use crate::bullet::Shot;
+use crate::{Bullet, GameObj, GameRng, GetHit, HitResult, PlayField, Position, Score, Step, StepResult, Unit, WouldHit};
+use crate::cannon::Cannon;
+use crate::config::{GameConfig, ShootProbability};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Aliens {
+    // the top left corner of the bounding box around all surviving aliens
     position: Position,
-    aliens: [[Option<Alien>; Aliens::ROWS]; Aliens::COLUMNS],
+    // indexed by column, then by row
+    aliens: Vec<Vec<Option<Alien>>>,
+    direction: MarchDirection,
+    ticks_since_march: usize,
+    wave: usize,
+    // the x coordinate Hard aliens aim at
+    target: Option<Unit>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MarchDirection {
+    Left,
+    Right,
+}
+
+impl MarchDirection {
+    pub fn reversed(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
 }
 
 impl Aliens {
-    pub const ROWS: usize = 5;
-    pub const COLUMNS: usize = 11;
     pub const GRID_GAP: Unit = Alien::WIDTH / 3;
+    /// How far the formation moves sideways in one march
+    pub const MARCH_STEP: Unit = 2;
+    /// How far the formation moves down, when it touches an edge of the [`PlayField`]
+    pub const DESCENT: Unit = Alien::HEIGHT;
+    /// How much more likely a Hard alien shoots, when its target is right below it
+    pub const AIMED_SHOT_FACTOR: f64 = 4.;
+    /// The maximum number of rows a new wave starts lower, than the first one
+    pub const MAX_WAVE_DESCENT: usize = 4;
 
-    pub fn new() -> Self {
-        const BASE_POSITION: Position = Position {
-            x: (PlayField::WIDTH - Aliens::WIDTH) / 2,
-            y: 0,
+    pub fn new(config: &GameConfig) -> Self {
+        Self::for_wave(config, 1)
+    }
+
+    /// Creates the formation for the given wave (starting at 1)
+    ///
+    /// Every wave starts one row lower than the previous one (up to [`Aliens::MAX_WAVE_DESCENT`]
+    /// rows) and marches faster.
+    pub fn for_wave(config: &GameConfig, wave: usize) -> Self {
+        let wave = wave.max(1);
+        let (width, _) = Self::formation_size(config.alien_rows, config.alien_columns);
+        // the top rows are left free for the mystery ship
+        let base_position = Position {
+            x: (PlayField::WIDTH - width) / 2,
+            y: Alien::HEIGHT * 2 + (wave - 1).min(Self::MAX_WAVE_DESCENT) * Self::DESCENT,
         };
 
         Self {
-            position: BASE_POSITION,
-            aliens: array_init::array_init(|col| {
-                array_init::array_init(|row| {
-                    Some(Alien {
-                        alien_type: AlienType::from_row(row),
-                        position: Position {
-                            x: BASE_POSITION.x + col * (Alien::WIDTH + Aliens::GRID_GAP),
-                            y: BASE_POSITION.y + row * (Alien::HEIGHT + Aliens::GRID_GAP),
-                        },
-                    })
+            position: base_position,
+            aliens: (0..config.alien_columns)
+                .map(|col| {
+                    (0..config.alien_rows)
+                        .map(|row| {
+                            Some(Alien {
+                                alien_type: AlienType::from_row(row, config.alien_rows),
+                                position: Position {
+                                    x: base_position.x + col * (Alien::WIDTH + Aliens::GRID_GAP),
+                                    y: base_position.y + row * (Alien::HEIGHT + Aliens::GRID_GAP),
+                                },
+                            })
+                        })
+                        .collect()
                 })
-            }),
+                .collect(),
+            direction: MarchDirection::Right,
+            ticks_since_march: 0,
+            wave,
+            target: None,
         }
     }
 
-    pub fn iter(&self) -> Iter<'_, [Option<Alien>; 5]> {
-        self.aliens.iter()
+    /// The width and height of a full formation
+    pub fn formation_size(rows: usize, columns: usize) -> (Unit, Unit) {
+        (
+            Alien::WIDTH * columns + columns.saturating_sub(1) * Self::GRID_GAP,
+            Alien::HEIGHT * rows + rows.saturating_sub(1) * Self::GRID_GAP,
+        )
     }
-}
 
-impl GameObj for Aliens {
-    const WIDTH: usize = Alien::WIDTH * Self::COLUMNS + (Self::COLUMNS - 1) * Self::GRID_GAP;
-    const HEIGHT: usize = Alien::HEIGHT * Self::ROWS + (Self::ROWS - 1) * Self::GRID_GAP;
+    pub fn iter(&self) -> Iter<'_, Vec<Option<Alien>>> {
+        self.aliens.iter()
+    }
 
-    fn position(&self) -> Position {
+    /// The top left corner of the bounding box around all surviving aliens
+    pub fn position(&self) -> Position {
         self.position
     }
-}
 
-impl Step for Aliens {
-    fn step(&mut self) -> StepResult {
-        // todo: move aliens
-        let mut one_survived = false;
-        let mut shots = Vec::new();
+    pub fn direction(&self) -> MarchDirection {
+        self.direction
+    }
+
+    /// The number of aliens, that are still alive
+    pub fn alive(&self) -> usize {
+        self.survivors().count()
+    }
 
+    /// The lowest surviving alien of each column
+    ///
+    /// Only these aliens are allowed to shoot, since the bullets of every other alien would
+    /// spawn inside the formation.
+    pub fn shooters(&self) -> impl Iterator<Item=&Alien> {
+        self.aliens
+            .iter()
+            .filter_map(|col| col.iter().rev().find_map(|alien| alien.as_ref()))
+    }
+
+    /// Whether the lowest surviving alien reached the row of the cannon
+    pub(crate) fn invaded(&self) -> bool {
+        matches!(self.bounds(), Some((_, bottom_right)) if bottom_right.y > Cannon::new().position().y)
+    }
+
+    /// Lets Hard aliens aim at the given x coordinate, i.e. the center of the [`Cannon`](crate::cannon::Cannon)
+    pub(crate) fn aim_at(&mut self, x: Unit) {
+        self.target = Some(x);
+    }
+
+    /// Rolls for every [shooter](Aliens::shooters), whether it shoots in the current step
+    pub(crate) fn shoot(&self, probabilities: &ShootProbability, rng: &mut GameRng) -> Vec<Bullet> {
+        let target = self.target;
+        self.shooters()
+            .filter_map(|alien| alien.shoot(probabilities.get(alien.alien_type), target, rng))
+            .collect()
+    }
+
+    /// The width of the bounding box around all surviving aliens
+    pub fn width(&self) -> Unit {
+        self.bounds()
+            .map(|(top_left, bottom_right)| bottom_right.x - top_left.x)
+            .unwrap_or(0)
+    }
+
+    /// The height of the bounding box around all surviving aliens
+    pub fn height(&self) -> Unit {
+        self.bounds()
+            .map(|(top_left, bottom_right)| bottom_right.y - top_left.y)
+            .unwrap_or(0)
+    }
+
+    fn survivors(&self) -> impl Iterator<Item=&Alien> {
+        self.aliens
+            .iter()
+            .flat_map(|col| col.iter())
+            .filter_map(|alien| alien.as_ref())
+    }
+
+    fn survivors_mut(&mut self) -> impl Iterator<Item=&mut Alien> {
         self.aliens
             .iter_mut()
-            .map(|row| row.iter_mut())
-            .flatten()
-            .filter(|opt| opt.is_some())
-            .map(|o| (o.as_mut().unwrap().step(), o))
-            .for_each(|(sr, o)| {
-                if sr.survived {
-                    one_survived = true;
-                    match sr.shot {
-                        Shot::One(bullet) => shots.push(bullet),
-                        Shot::Many(bullets) => shots.extend(bullets),
-                        Shot::None => {}
-                    }
-                } else {
-                    *o = None;
-                }
+            .flat_map(|col| col.iter_mut())
+            .filter_map(|alien| alien.as_mut())
+    }
+
+    /// Returns the top left and the (exclusive) bottom right corner of the bounding box
+    /// around all surviving aliens
+    fn bounds(&self) -> Option<(Position, Position)> {
+        self.survivors()
+            .fold(None, |bounds: Option<(Position, Position)>, alien| {
+                let (mut top_left, mut bottom_right) = bounds.unwrap_or((alien.position, alien.position));
+
+                top_left.x = top_left.x.min(alien.position.x);
+                top_left.y = top_left.y.min(alien.position.y);
+                bottom_right.x = bottom_right.x.max(alien.position.x + Alien::WIDTH);
+                bottom_right.y = bottom_right.y.max(alien.position.y + Alien::HEIGHT);
+
+                Some((top_left, bottom_right))
+            })
+    }
+
+    /// The number of steps between two marches
+    ///
+    /// The formation gets faster, the fewer aliens survive and the higher the wave is. With a
+    /// full grid in the first wave it marches about once every second, the last alien marches
+    /// every step.
+    fn march_interval(&self) -> usize {
+        1 + self.alive() / (1 + self.wave)
+    }
+
+    /// Moves the whole formation one step sideways, or one row down, if it touches an edge
+    /// of the [`PlayField`]
+    fn march(&mut self) {
+        let (top_left, bottom_right) = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        let touches_edge = match self.direction {
+            MarchDirection::Left => top_left.x < Self::MARCH_STEP,
+            MarchDirection::Right => bottom_right.x + Self::MARCH_STEP > PlayField::WIDTH,
+        };
+
+        let direction = self.direction;
+        self.survivors_mut()
+            .for_each(|alien| match (touches_edge, direction) {
+                (true, _) => alien.position.y += Self::DESCENT,
+                (false, MarchDirection::Left) => alien.position.x -= Self::MARCH_STEP,
+                (false, MarchDirection::Right) => alien.position.x += Self::MARCH_STEP,
             });
 
+        if touches_edge {
+            self.direction = direction.reversed();
+        }
+    }
+}
+
+/// Only marches, the aliens shoot with [`Aliens::shoot`], since the shoot probabilities are
+/// part of the [`GameConfig`]
+impl Step for Aliens {
+    fn step(&mut self, _rng: &mut GameRng) -> StepResult {
+        self.ticks_since_march += 1;
+        if self.ticks_since_march >= self.march_interval() {
+            self.ticks_since_march = 0;
+            self.march();
+        }
+
+        if let Some((top_left, _)) = self.bounds() {
+            self.position = top_left;
+        }
+
         StepResult {
-            survived: one_survived,
-            shot: Shot::Many(shots),
+            survived: self.alive() > 0,
         }
     }
 }
@@ -89,8 +249,7 @@ impl WouldHit<Option<Alien>> for Aliens {
     fn would_hit(&mut self, bullet: &Bullet) -> Option<&mut Option<Alien>> {
         self.aliens
             .iter_mut()
-            .map(|row| row.iter_mut())
-            .flatten()
+            .flat_map(|row| row.iter_mut())
             .find_map(|alien| {
                 alien
                     .as_mut()
@@ -101,16 +260,49 @@ impl WouldHit<Option<Alien>> for Aliens {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Alien {
     alien_type: AlienType,
     position: Position,
 }
 
 impl Alien {
+    /// Creates a mystery ship at the left edge of the top row of the [`PlayField`]
+    pub(crate) fn mystery() -> Self {
+        Self {
+            alien_type: AlienType::Mystery,
+            position: Position { x: 0, y: 0 },
+        }
+    }
+
     pub fn alien_type(&self) -> AlienType {
         self.alien_type
     }
+
+    /// Rolls, whether this alien shoots in the current step
+    ///
+    /// Hard aliens are more likely to shoot, when the `target` is right below them.
+    fn shoot(&self, mut probability: f64, target: Option<Unit>, rng: &mut GameRng) -> Option<Bullet> {
+        let aimed = matches!(self.alien_type, AlienType::Hard) && matches!(
+            target,
+            Some(x) if (self.position.x..self.position.x + Self::WIDTH).contains(&x)
+        );
+
+        if aimed {
+            probability = (probability * Aliens::AIMED_SHOT_FACTOR).min(1.);
+        }
+
+        rng
+            .gen_bool(probability)
+            .then(|| Bullet::alien_at_position(
+                Position {
+                    x: self.position.x + Self::WIDTH / 2,
+                    y: self.position.y + Self::HEIGHT,
+                },
+                self.alien_type,
+            ))
+    }
 }
 
 impl GameObj for Alien {
@@ -123,31 +315,16 @@ impl GameObj for Alien {
 }
 
 impl Step for Alien {
-    fn step(&mut self) -> StepResult {
+    fn step(&mut self, _rng: &mut GameRng) -> StepResult {
+        // shooting is decided by the formation, since only the lowest alien of a column may shoot
         if let AlienType::Mystery = self.alien_type {
             self.position.x += 1;
             return StepResult {
                 survived: PlayField::overlaps(self),
-                shot: Shot::None,
             };
         }
 
-        // todo: only shoot, if the Alien is lowest in it's column
-        let shot = rand::thread_rng()
-            .gen_bool(self.alien_type.shoot_probability())
-            .then(|| Shot::One(Bullet::alien_at_position(
-                Position {
-                    x: self.position.x + Self::WIDTH / 2,
-                    y: self.position.y + Self::HEIGHT,
-                },
-                self.alien_type,
-            )))
-            .unwrap_or(Shot::None);
-
-        StepResult {
-            survived: true,
-            shot,
-        }
+        StepResult::default()
     }
 }
 
@@ -161,17 +338,18 @@ impl WouldHit<Alien> for Alien {
 }
 
 impl GetHit for Alien {
-    fn hit(&mut self, bullet: &Bullet, score: &mut i64) -> HitResult {
+    fn hit(&mut self, bullet: &Bullet, score: &mut i64, rng: &mut GameRng) -> HitResult {
         if bullet.is_alien_bullet() {
             HitResult { survived: true, absorbed_bullet: false }
         } else {
-            *score += self.alien_type.points();
+            *score += self.alien_type.points(rng);
             HitResult { survived: false, absorbed_bullet: true }
         }
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AlienType {
     Mystery,
     Hard,
@@ -180,30 +358,236 @@ pub enum AlienType {
 }
 
 impl AlienType {
-    pub fn from_row(row: usize) -> Self {
+    /// The top row consists of Hard aliens, the upper half of the remaining rows of Medium
+    /// aliens and the rest of Easy aliens
+    pub fn from_row(row: usize, rows: usize) -> Self {
         match row {
             0 => Self::Hard,
-            1..=2 => Self::Medium,
-            3..=4 => Self::Easy,
-            _ => unimplemented!("there may only be 5 rows of aliens")
+            row if row <= rows / 2 => Self::Medium,
+            _ => Self::Easy,
         }
     }
 
-    pub fn points(&self) -> i64 {
+    pub fn points(&self, rng: &mut GameRng) -> Score {
         match self {
-            Self::Mystery => rand::thread_rng().gen_range(10..100),
+            Self::Mystery => rng.gen_range(10..100),
             Self::Hard => 30,
             Self::Medium => 20,
             Self::Easy => 10
         }
     }
 
-    pub fn shoot_probability(&self) -> f64 {
-        match self {
-            Self::Mystery => 0.,
-            Self::Hard => 0.001,
-            Self::Medium => 0.0008,
-            Self::Easy => 0.0005
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn column_of(aliens: &Aliens, alien: &Alien) -> usize {
+        (alien.position.x - aliens.position.x) / (Alien::WIDTH + Aliens::GRID_GAP)
+    }
+
+    fn row_of(aliens: &Aliens, alien: &Alien) -> usize {
+        (alien.position.y - aliens.position.y) / (Alien::HEIGHT + Aliens::GRID_GAP)
+    }
+
+    const ROWS: usize = 5;
+
+    fn aliens() -> Aliens {
+        let config = GameConfig::default();
+        assert_eq!(config.alien_rows, ROWS);
+        Aliens::new(&config)
+    }
+
+    #[test]
+    fn bottom_row_shoots_in_full_grid() {
+        let aliens = aliens();
+        let shooters = aliens.shooters().collect::<Vec<_>>();
+
+        assert_eq!(shooters.len(), GameConfig::default().alien_columns);
+        for (col, alien) in shooters.into_iter().enumerate() {
+            assert_eq!(column_of(&aliens, alien), col);
+            assert_eq!(row_of(&aliens, alien), ROWS - 1);
+        }
+    }
+
+    #[test]
+    fn next_alien_up_shoots_after_lowest_died() {
+        let mut aliens = aliens();
+        aliens.aliens[3][ROWS - 1] = None;
+        aliens.aliens[3][ROWS - 2] = None;
+
+        let shooter = aliens
+            .shooters()
+            .find(|alien| column_of(&aliens, alien) == 3)
+            .unwrap();
+        assert_eq!(row_of(&aliens, shooter), ROWS - 3);
+    }
+
+    #[test]
+    fn gaps_above_the_lowest_alien_do_not_matter() {
+        let mut aliens = aliens();
+        aliens.aliens[7][0] = None;
+        aliens.aliens[7][2] = None;
+
+        let shooter = aliens
+            .shooters()
+            .find(|alien| column_of(&aliens, alien) == 7)
+            .unwrap();
+        assert_eq!(row_of(&aliens, shooter), ROWS - 1);
+    }
+
+    #[test]
+    fn empty_column_does_not_shoot() {
+        let mut aliens = aliens();
+        aliens.aliens[5] = vec![None; ROWS];
+
+        let columns = aliens
+            .shooters()
+            .map(|alien| column_of(&aliens, alien))
+            .collect::<Vec<_>>();
+        assert_eq!(columns, [0, 1, 2, 3, 4, 6, 7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn hard_alien_shoots_when_alone_in_column() {
+        let mut aliens = aliens();
+        for row in 1..ROWS {
+            aliens.aliens[0][row] = None;
+        }
+
+        let shooter = aliens.shooters().next().unwrap();
+        assert_eq!(column_of(&aliens, shooter), 0);
+        assert!(matches!(shooter.alien_type(), AlienType::Hard));
+    }
+
+    #[test]
+    fn waves_start_lower_up_to_the_max_descent() {
+        let config = GameConfig::default();
+        let top = |wave| Aliens::for_wave(&config, wave).position().y;
+
+        for wave in 1..=Aliens::MAX_WAVE_DESCENT {
+            assert_eq!(top(wave + 1), top(wave) + Aliens::DESCENT);
+        }
+        let lowest = top(Aliens::MAX_WAVE_DESCENT + 1);
+        assert_eq!(lowest, top(1) + Aliens::MAX_WAVE_DESCENT * Aliens::DESCENT);
+        assert_eq!(top(Aliens::MAX_WAVE_DESCENT + 2), lowest);
+        assert_eq!(top(100), lowest);
+    }
+
+    // moves the whole formation down, like marching does
+    fn descend(aliens: &mut Aliens, dy: Unit) {
+        aliens.position.y += dy;
+        aliens.aliens
+            .iter_mut()
+            .flatten()
+            .flatten()
+            .for_each(|alien| alien.position.y += dy);
+    }
+
+    #[test]
+    fn invasion_is_decided_by_the_lowest_surviving_alien() {
+        let mut aliens = aliens();
+        // only the bottom row survives, so the formation is much lower than its top left corner
+        for column in &mut aliens.aliens {
+            column[..ROWS - 1].iter_mut().for_each(|alien| *alien = None);
         }
+
+        let bottom = aliens.shooters().next().unwrap().position.y + Alien::HEIGHT;
+        descend(&mut aliens, Cannon::new().position().y - bottom);
+        assert!(!aliens.invaded());
+
+        descend(&mut aliens, 1);
+        assert!(aliens.invaded());
+    }
+
+    #[test]
+    fn formation_marches_sideways_once_per_interval() {
+        let mut aliens = aliens();
+        let mut rng = GameRng::seed_from_u64(0);
+        let start = aliens.position();
+
+        for _ in 1..aliens.march_interval() {
+            aliens.step(&mut rng);
+        }
+        assert_eq!(aliens.position(), start);
+
+        aliens.step(&mut rng);
+        assert_eq!(aliens.position(), Position { x: start.x + Aliens::MARCH_STEP, y: start.y });
+    }
+
+    #[test]
+    fn formation_descends_and_reverses_at_each_edge() {
+        let mut aliens = aliens();
+
+        for direction in [MarchDirection::Right, MarchDirection::Left] {
+            assert_eq!(aliens.direction(), direction);
+            while aliens.direction() == direction {
+                let (before, _) = aliens.bounds().unwrap();
+                aliens.march();
+                let (after, bottom_right) = aliens.bounds().unwrap();
+
+                assert!(bottom_right.x <= PlayField::WIDTH);
+                match aliens.direction() == direction {
+                    true => assert_eq!(after.y, before.y),
+                    false => assert_eq!(after, Position { x: before.x, y: before.y + Aliens::DESCENT }),
+                }
+            }
+        }
+        assert_eq!(aliens.direction(), MarchDirection::Right);
+    }
+
+    #[test]
+    fn position_is_the_top_left_corner_of_the_survivors() {
+        let mut aliens = aliens();
+        let mut rng = GameRng::seed_from_u64(0);
+        let start = aliens.position();
+
+        aliens.aliens[0] = vec![None; ROWS];
+        aliens.aliens.iter_mut().for_each(|column| column[0] = None);
+        aliens.step(&mut rng);
+
+        assert_eq!(aliens.position(), Position {
+            x: start.x + Alien::WIDTH + Aliens::GRID_GAP,
+            y: start.y + Alien::HEIGHT + Aliens::GRID_GAP,
+        });
+        assert_eq!(Some(aliens.position()), aliens.bounds().map(|(top_left, _)| top_left));
+    }
+
+    #[test]
+    fn march_interval_shrinks_with_fewer_aliens_and_higher_waves() {
+        let config = GameConfig::default();
+        let mut aliens = Aliens::new(&config);
+        let full = aliens.march_interval();
+
+        assert!(Aliens::for_wave(&config, 2).march_interval() < full);
+        assert!(Aliens::for_wave(&config, 5).march_interval() < Aliens::for_wave(&config, 2).march_interval());
+
+        aliens.aliens[0] = vec![None; ROWS];
+        assert!(aliens.march_interval() < full);
+
+        aliens.aliens
+            .iter_mut()
+            .flatten()
+            .filter(|alien| alien.is_some())
+            .skip(1)
+            .for_each(|alien| *alien = None);
+        assert_eq!(aliens.alive(), 1);
+        assert_eq!(aliens.march_interval(), 1);
+    }
+
+    #[test]
+    fn empty_formation_does_not_invade() {
+        let mut aliens = aliens();
+        let dy = Cannon::new().position().y - aliens.position().y;
+        descend(&mut aliens, dy);
+        assert!(aliens.invaded());
+
+        aliens.aliens.iter_mut().flatten().for_each(|alien| *alien = None);
+        assert!(!aliens.invaded());
     }
 }
@@ -1,24 +1,23 @@
-use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-use crate::{GameObj, Position, Step, StepResult};
+use crate::{GameObj, GameRng, Player, Position, Step, StepResult};
 use crate::alien::AlienType;
 
-pub enum Shot {
-    One(Bullet),
-    Many(Vec<Bullet>),
-    None,
-}
-
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Bullet {
     position: Position,
     direction: BulletDirection,
     // just in case, we want different bullets for different aliens
     // otherwise, this field is useless
     alien_type: Option<AlienType>,
+    // the player, that fired the bullet
+    player: Option<Player>,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BulletDirection {
     Upwards,
     Downwards,
@@ -33,11 +32,17 @@ impl Bullet {
         self.direction
     }
 
-    pub(crate) fn player_at_position(position: Position) -> Self {
+    /// The player, that fired the bullet, or `None` for alien bullets
+    pub fn player(&self) -> Option<Player> {
+        self.player
+    }
+
+    pub(crate) fn player_at_position(position: Position, player: Player) -> Self {
         Self {
             position,
             direction: BulletDirection::Upwards,
             alien_type: None,
+            player: Some(player),
         }
     }
 
@@ -46,6 +51,7 @@ impl Bullet {
             position,
             direction: BulletDirection::Downwards,
             alien_type: Some(alien_type),
+            player: None,
         }
     }
 
@@ -71,7 +77,7 @@ impl GameObj for Bullet {
 }
 
 impl Step for Bullet {
-    fn step(&mut self) -> StepResult {
+    fn step(&mut self, _rng: &mut GameRng) -> StepResult {
         let y = match self.direction {
             BulletDirection::Upwards => self.position.y.checked_sub(1),
             BulletDirection::Downwards => self.position.y.checked_add(1),
@@ -85,9 +91,6 @@ impl Step for Bullet {
             None => false
         };
 
-        StepResult {
-            survived,
-            shot: Shot::None,
-        }
+        StepResult { survived }
     }
 }
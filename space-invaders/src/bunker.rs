@@ -1,48 +1,91 @@
+use alloc::vec::Vec;
 use core::slice::Iter;
 
-use crate::{Bullet, GameObj, GetHit, HitResult, PlayField, Position, Unit, WouldHit};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Bullet, GameObj, GameRng, GetHit, HitResult, PlayField, Position, Unit, WouldHit};
 use crate::bullet::BulletDirection;
 use crate::cannon::Cannon;
+use crate::config::GameConfig;
+use crate::raster;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Bunkers {
     position: Position,
-    bunkers: [Option<Bunker>; Bunkers::BUNKERS],
+    bunkers: Vec<Option<Bunker>>,
 }
 
 impl Bunkers {
-    pub const BUNKERS: usize = 4;
     pub const GRID_GAP: Unit = Bunker::WIDTH;
 
-    pub fn new() -> Self {
-        const BASE_POSITION: Position = Position {
-            x: (PlayField::WIDTH - Bunkers::WIDTH) / 2,
+    pub fn new(config: &GameConfig) -> Self {
+        let base_position = Position {
+            x: (PlayField::WIDTH - Self::width_of(config.bunkers)) / 2,
             y: PlayField::HEIGHT - (Cannon::HEIGHT * 5),
         };
 
         Self {
-            position: BASE_POSITION,
-            bunkers: array_init::array_init(|col| {
-                Some(Bunker::at_position(Position {
-                    x: BASE_POSITION.x + col * (Bunker::WIDTH + Bunkers::GRID_GAP),
-                    y: BASE_POSITION.y,
-                }))
-            }),
+            position: base_position,
+            bunkers: (0..config.bunkers)
+                .map(|col| {
+                    Some(Bunker::at_position(
+                        Position {
+                            x: base_position.x + col * (Bunker::WIDTH + Bunkers::GRID_GAP),
+                            y: base_position.y,
+                        },
+                        config.bunker_erosion,
+                    ))
+                })
+                .collect(),
         }
     }
 
+    /// The width of a row of `bunkers` bunkers
+    pub fn width_of(bunkers: usize) -> Unit {
+        Bunker::WIDTH * bunkers + bunkers.saturating_sub(1) * Self::GRID_GAP
+    }
+
     pub fn iter(&self) -> Iter<'_, Option<Bunker>> {
         self.bunkers.iter()
     }
-}
-
-impl GameObj for Bunkers {
-    const WIDTH: usize = Bunker::WIDTH * Self::BUNKERS + (Self::BUNKERS - 1) * Self::GRID_GAP;
-    const HEIGHT: usize = Bunker::HEIGHT;
 
-    fn position(&self) -> Position {
+    /// The top left corner of the row of bunkers
+    pub fn position(&self) -> Position {
         self.position
     }
+
+    pub fn width(&self) -> Unit {
+        Self::width_of(self.bunkers.len())
+    }
+
+    pub fn height(&self) -> Unit {
+        Bunker::HEIGHT
+    }
+
+    /// The number of intact pixels of all bunkers
+    pub fn pixels(&self) -> usize {
+        self.bunkers
+            .iter()
+            .flatten()
+            .map(|bunker| bunker.pixels())
+            .sum()
+    }
+
+    /// Removes all pixels inside the given (absolute) rectangle from every bunker
+    pub(crate) fn erase(&mut self, position: Position, width: Unit, height: Unit) {
+        if !position.overlaps(width, height, self.position, self.width(), self.height()) { return; }
+
+        for bunker in self.bunkers.iter_mut() {
+            if let Some(b) = bunker {
+                b.erase(position, width, height);
+                if b.is_destroyed() {
+                    *bunker = None;
+                }
+            }
+        }
+    }
 }
 
 impl WouldHit<Option<Bunker>> for Bunkers {
@@ -59,34 +102,112 @@ impl WouldHit<Option<Bunker>> for Bunkers {
     }
 }
 
-#[derive(Clone, Debug)]
+/// A bunker, that erodes pixel by pixel
+#[derive(Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Bunker {
     position: Position,
-    stable: [[u8; 3]; 3],
+    // one bit per pixel, the lowest bit is the leftmost pixel of a row
+    mask: [u32; Bunker::HEIGHT],
+    // how deep a bullet chews into the bunker
+    erosion: Unit,
 }
 
 impl Bunker {
-    const STABILITY: [[u8; 3]; 3] = [
-        [2, 2, 2],
-        [2, 0, 2],
-        [2, 0, 2],
-    ];
-
-    pub fn at_position(position: Position) -> Self {
+    pub fn at_position(position: Position, erosion: Unit) -> Self {
         Self {
             position,
-            stable: Self::STABILITY,
+            mask: Self::intact_mask(),
+            erosion,
+        }
+    }
+
+    /// The pixels of an undamaged bunker, in the shape of [`raster::BUNKER`]
+    pub fn intact_mask() -> [u32; Bunker::HEIGHT] {
+        let mut mask = [0; Bunker::HEIGHT];
+        for rect in raster::BUNKER {
+            for row in &mut mask[rect.y..rect.y + rect.height] {
+                *row |= ((1 << rect.width) - 1) << rect.x;
+            }
+        }
+
+        mask
+    }
+
+    /// The remaining pixels, one row per element and one bit per pixel, where the lowest bit
+    /// is the leftmost pixel
+    pub fn mask(&self) -> &[u32; Bunker::HEIGHT] {
+        &self.mask
+    }
+
+    /// Whether the pixel at the given (absolute) position is intact
+    pub fn is_solid(&self, Position { x, y }: Position) -> bool {
+        let x = match x.checked_sub(self.position.x) {
+            Some(x) if x < Self::WIDTH => x,
+            _ => return false,
+        };
+
+        match y.checked_sub(self.position.y) {
+            Some(y) if y < Self::HEIGHT => self.mask[y] & 1 << x != 0,
+            _ => false,
+        }
+    }
+
+    /// The number of intact pixels
+    pub fn pixels(&self) -> usize {
+        self.mask
+            .iter()
+            .map(|row| row.count_ones() as usize)
+            .sum()
+    }
+
+    /// The fraction of intact pixels in each of the 3x3 cells of the bunker, from `0`
+    /// (destroyed) to `1` (undamaged)
+    pub fn health(&self) -> [[f32; 3]; 3] {
+        const CELL_WIDTH: Unit = Bunker::WIDTH / 3;
+        const CELL_HEIGHT: Unit = Bunker::HEIGHT / 3;
+        const CELL: u32 = (1 << CELL_WIDTH) - 1;
+
+        let intact = Self::intact_mask();
+        let mut health = [[0.; 3]; 3];
+        for (y, health) in health.iter_mut().enumerate() {
+            for (x, health) in health.iter_mut().enumerate() {
+                let rows = y * CELL_HEIGHT..(y + 1) * CELL_HEIGHT;
+                let count = |mask: &[u32]| -> u32 {
+                    mask[rows.clone()]
+                        .iter()
+                        .map(|row| (row >> (x * CELL_WIDTH) & CELL).count_ones())
+                        .sum()
+                };
+
+                *health = match count(&intact) {
+                    0 => 0.,
+                    intact => count(&self.mask) as f32 / intact as f32,
+                };
+            }
+        }
+
+        health
+    }
+
+    /// Removes all pixels inside the given (absolute) rectangle
+    pub(crate) fn erase(&mut self, Position { x, y }: Position, width: Unit, height: Unit) {
+        let left = x.max(self.position.x);
+        let right = (x + width).min(self.position.x + Self::WIDTH);
+        let top = y.max(self.position.y);
+        let bottom = (y + height).min(self.position.y + Self::HEIGHT);
+        if left >= right || top >= bottom { return; }
+
+        let columns = ((1 << (right - left)) - 1) << (left - self.position.x);
+        for row in &mut self.mask[top - self.position.y..bottom - self.position.y] {
+            *row &= !columns;
         }
     }
 
     fn is_destroyed(&self) -> bool {
-        self.stable
+        self.mask
             .iter()
-            .all(|row| {
-                row
-                    .iter()
-                    .all(|&stability| stability == 0)
-            })
+            .all(|&row| row == 0)
     }
 }
 
@@ -99,24 +220,24 @@ impl GameObj for Bunker {
     }
 }
 
+/// Only the leading pixel of a bullet can hit a bunker, so bullets travel through holes, that
+/// are at least one pixel wide
 impl WouldHit<Bunker> for Bunker {
     fn would_hit(&mut self, bullet: &Bullet) -> Option<&mut Bunker> {
-        if !self.overlaps(bullet) || bullet.position().y < self.position.y { return None; }
-
-        let x = (bullet.directional_position().x - self.position.x) / (Self::WIDTH / 3);
-        let y = (bullet.directional_position().y - self.position.y) / (Self::HEIGHT / 3);
-        if x == 3 || y == 3 { return None; }
-
-        (self.stable[y][x] > 0)
+        self.is_solid(bullet.directional_position())
             .then_some(self)
     }
 }
 
+/// Bullets chew a three pixel wide hole into the bunker, in the direction they travel
 impl GetHit for Bunker {
-    fn hit(&mut self, bullet: &Bullet, _score: &mut i64) -> HitResult {
-        let x = (bullet.directional_position().x - self.position.x) / (Self::WIDTH / 3);
-        let y = (bullet.directional_position().y - self.position.y) / (Self::HEIGHT / 3);
-        self.stable[y][x] -= 1;
+    fn hit(&mut self, bullet: &Bullet, _score: &mut i64, _rng: &mut GameRng) -> HitResult {
+        let impact = bullet.directional_position();
+        let top = match bullet.direction() {
+            BulletDirection::Upwards => (impact.y + 1).saturating_sub(self.erosion),
+            BulletDirection::Downwards => impact.y,
+        };
+        self.erase(Position { x: impact.x.saturating_sub(1), y: top }, 3, self.erosion);
 
         HitResult {
             survived: !self.is_destroyed(),
@@ -124,3 +245,95 @@ impl GetHit for Bunker {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::alien::AlienType;
+    use crate::Player;
+
+    use super::*;
+
+    const ORIGIN: Position = Position { x: 100, y: 50 };
+
+    // the intact mask without the pixels in the given (relative) rectangle
+    fn without(x: Unit, y: Unit, width: Unit, height: Unit) -> [u32; Bunker::HEIGHT] {
+        let mut mask = Bunker::intact_mask();
+        for row in &mut mask[y..y + height] {
+            *row &= !(((1 << width) - 1) << x);
+        }
+
+        mask
+    }
+
+    fn hit(bunker: &mut Bunker, bullet: &Bullet) {
+        assert!(bunker.would_hit(bullet).is_some());
+        assert!(bunker.hit(bullet, &mut 0, &mut GameRng::seed_from_u64(0)).absorbed_bullet);
+    }
+
+    // a player bullet, whose top pixel is in the bottom row of the left leg
+    fn from_below() -> Bullet {
+        let impact = Position { x: ORIGIN.x + 3, y: ORIGIN.y + Bunker::HEIGHT - 1 };
+        Bullet::player_at_position(impact, Player::One)
+    }
+
+    // an alien bullet, whose bottom pixel is in the top row of the bunker
+    fn from_above() -> Bullet {
+        let impact = Position { x: ORIGIN.x + 12, y: ORIGIN.y + 1 - Bullet::HEIGHT };
+        Bullet::alien_at_position(impact, AlienType::Easy)
+    }
+
+    #[test]
+    fn bullets_from_below_erode_upwards() {
+        for erosion in [1, 4, 6] {
+            let mut bunker = Bunker::at_position(ORIGIN, erosion);
+            hit(&mut bunker, &from_below());
+
+            assert_eq!(bunker.mask(), &without(2, Bunker::HEIGHT - erosion, 3, erosion));
+            assert_eq!(bunker.pixels(), Bunker::at_position(ORIGIN, erosion).pixels() - 3 * erosion);
+        }
+    }
+
+    #[test]
+    fn bullets_from_above_erode_downwards() {
+        for erosion in [1, 4, 6] {
+            let mut bunker = Bunker::at_position(ORIGIN, erosion);
+            hit(&mut bunker, &from_above());
+
+            assert_eq!(bunker.mask(), &without(11, 0, 3, erosion));
+        }
+    }
+
+    #[test]
+    fn health_drops_only_in_the_hit_cells() {
+        let mut bunker = Bunker::at_position(ORIGIN, 4);
+        // the bottom center cell is the empty arch
+        let intact = [[1., 1., 1.], [1., 1., 1.], [1., 0., 1.]];
+        assert_eq!(bunker.health(), intact);
+
+        hit(&mut bunker, &from_below());
+        hit(&mut bunker, &from_above());
+
+        let health = bunker.health();
+        for (y, row) in health.iter().enumerate() {
+            for (x, &health) in row.iter().enumerate() {
+                match (x, y) {
+                    // the left leg lost 12 of its 36 pixels in the bottom cell
+                    (0, 2) => assert_eq!(health, 24. / 36.),
+                    (1, 0) => assert!(health < 1.),
+                    _ => assert_eq!(health, intact[y][x]),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn destroyed_bunker_has_no_health() {
+        let mut bunker = Bunker::at_position(ORIGIN, 4);
+        bunker.erase(ORIGIN, Bunker::WIDTH, Bunker::HEIGHT);
+
+        assert_eq!(bunker.pixels(), 0);
+        assert_eq!(bunker.health(), [[0.; 3]; 3]);
+    }
+}
@@ -1,8 +1,21 @@
-use crate::{Bullet, GameObj, GetHit, PlayField, Position, WouldHit};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+use crate::{Bullet, GameObj, GetHit, PlayField, Player, Position, Unit, WouldHit};
+
+#[derive(Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Cannon {
-    position: Position
+    position: Position,
+    // the number of steps until the cannon can fire again
+    cooldown: usize,
+}
+
+/// A cannon in the center of the bottom of the field
+impl Default for Cannon {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Cannon {
@@ -13,27 +26,49 @@ impl Cannon {
         };
 
         Self {
-            position: BASE_POSITION
+            position: BASE_POSITION,
+            cooldown: 0,
         }
     }
 
-    pub(crate) fn move_right(&mut self) {
-        if self.position.x + Self::WIDTH < PlayField::WIDTH - 1 {
-            self.position.x += 1;
+    /// A cannon, whose center is at `x`
+    pub(crate) fn centered_at(x: Unit) -> Self {
+        Self {
+            position: Position {
+                x: x - Self::WIDTH / 2,
+                y: PlayField::HEIGHT - Self::HEIGHT,
+            },
+            cooldown: 0,
         }
     }
 
-    pub(crate) fn move_left(&mut self) {
-        if self.position.x > 0 {
-            self.position.x -= 1;
-        }
+    /// The number of steps until the cannon can fire again
+    pub fn cooldown(&self) -> usize {
+        self.cooldown
+    }
+
+    pub(crate) fn move_right(&mut self, speed: Unit) {
+        self.position.x = (self.position.x + speed).min(PlayField::WIDTH - 1 - Self::WIDTH);
+    }
+
+    pub(crate) fn move_left(&mut self, speed: Unit) {
+        self.position.x = self.position.x.saturating_sub(speed);
+    }
+
+    pub(crate) fn cool_down(&mut self) {
+        self.cooldown = self.cooldown.saturating_sub(1);
     }
 
-    pub(crate) fn shoot(&self) -> Bullet {
-        Bullet::player_at_position(Position {
-            x: self.position.x + Self::WIDTH / 2,
-            y: self.position.y + 1,
-        })
+    /// Fires a bullet, after which the cannon can't fire for `cooldown` steps
+    pub(crate) fn shoot(&mut self, cooldown: usize, player: Player) -> Bullet {
+        self.cooldown = cooldown;
+        Bullet::player_at_position(
+            Position {
+                x: self.position.x + Self::WIDTH / 2,
+                y: self.position.y + 1,
+            },
+            player,
+        )
     }
 }
 
@@ -0,0 +1,309 @@
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{GameObj, PlayField, Score, Unit};
+use crate::alien::{Alien, AlienType, Aliens};
+use crate::bunker::Bunkers;
+use crate::cannon::Cannon;
+use crate::replay::Fnv1a;
+
+/// The rules of a game
+///
+/// Tournaments can define rule variants, like "one life", "no bunkers" or "double fire rate",
+/// by changing single fields of the [default](GameConfig::default) config. When deserialized,
+/// every missing field falls back to its default value.
+///
+/// The config is part of the [`PlayField::checksum`] and of every
+/// [`Replay`](crate::replay::Replay), so the exact ruleset of a game can always be verified.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GameConfig {
+    pub alien_rows: usize,
+    pub alien_columns: usize,
+    pub lives: usize,
+    pub bunkers: usize,
+    /// How many pixels deep a bullet chews into a bunker
+    pub bunker_erosion: Unit,
+    /// The probability, that an alien at the bottom of its column shoots in one step
+    pub shoot_probability: ShootProbability,
+    /// How far the cannon moves in one step
+    pub cannon_speed: Unit,
+    /// The maximum number of player bullets on the field at the same time
+    ///
+    /// The arcade rule is one bullet. With `None`, the number of bullets is unlimited.
+    pub max_player_bullets: Option<usize>,
+    /// The number of steps after a shot, in which the cannon can't fire
+    pub fire_cooldown: usize,
+    /// The number of steps the game freezes, after the cannon was hit
+    pub respawn_ticks: usize,
+    /// Whether overlapping player and alien bullets destroy each other
+    pub bullet_collisions: bool,
+    /// The points for every alien bullet, that is shot down by a player bullet
+    pub bullet_collision_score: Score,
+    /// The number of steps between the despawn of a mystery ship and the spawn of the next one
+    pub mystery_interval: usize,
+    /// The number of waves, after which the game is won
+    ///
+    /// With `None`, new waves spawn forever.
+    pub max_waves: Option<usize>,
+}
+
+impl GameConfig {
+    /// Checks, that the config describes a playable game
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let (formation_width, formation_height) = Aliens::formation_size(self.alien_rows, self.alien_columns);
+
+        if self.alien_rows == 0 || self.alien_columns == 0 {
+            return Err(ConfigError::Invalid("there has to be at least one alien"));
+        }
+        if formation_width > PlayField::WIDTH {
+            return Err(ConfigError::Invalid("the alien formation is wider than the play field"));
+        }
+        if Alien::HEIGHT * 2 + formation_height > Cannon::new().position().y {
+            return Err(ConfigError::Invalid("the alien formation is taller than the play field"));
+        }
+        if self.lives == 0 {
+            return Err(ConfigError::Invalid("there has to be at least one life"));
+        }
+        if Bunkers::width_of(self.bunkers) > PlayField::WIDTH {
+            return Err(ConfigError::Invalid("the bunkers are wider than the play field"));
+        }
+        if self.bunker_erosion == 0 {
+            return Err(ConfigError::Invalid("the bunkers have to be able to take damage"));
+        }
+        let probabilities = &self.shoot_probability;
+        if [probabilities.hard, probabilities.medium, probabilities.easy]
+            .iter()
+            .any(|probability| !(0. ..=1.).contains(probability)) {
+            return Err(ConfigError::Invalid("shoot probabilities have to be between 0 and 1"));
+        }
+        if self.cannon_speed == 0 {
+            return Err(ConfigError::Invalid("the cannon has to be able to move"));
+        }
+        if self.max_player_bullets == Some(0) {
+            return Err(ConfigError::Invalid("the cannon has to be able to fire"));
+        }
+        if self.max_waves == Some(0) {
+            return Err(ConfigError::Invalid("there has to be at least one wave"));
+        }
+
+        Ok(())
+    }
+
+    /// A hash of the config, that is identical on every platform
+    ///
+    /// Match organisers can publish it, to pin the exact ruleset of a competition.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Parses and validates a config in JSON format
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self, ConfigError> {
+        let config: Self = serde_json::from_str(json).map_err(ConfigError::Json)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parses and validates a config in TOML format
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(toml).map_err(ConfigError::Toml)?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// The rules of the original game
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            alien_rows: 5,
+            alien_columns: 11,
+            lives: 3,
+            bunkers: 4,
+            bunker_erosion: 4,
+            shoot_probability: ShootProbability {
+                hard: 0.001,
+                medium: 0.0008,
+                easy: 0.0005,
+            },
+            cannon_speed: 1,
+            max_player_bullets: Some(1),
+            fire_cooldown: 0,
+            respawn_ticks: 60,
+            bullet_collisions: true,
+            bullet_collision_score: 0,
+            mystery_interval: 600,
+            max_waves: None,
+        }
+    }
+}
+
+// probabilities are hashed by their bit patterns, which are identical on every platform
+impl Hash for GameConfig {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.alien_rows.hash(state);
+        self.alien_columns.hash(state);
+        self.lives.hash(state);
+        self.bunkers.hash(state);
+        self.bunker_erosion.hash(state);
+        self.shoot_probability.hard.to_bits().hash(state);
+        self.shoot_probability.medium.to_bits().hash(state);
+        self.shoot_probability.easy.to_bits().hash(state);
+        self.cannon_speed.hash(state);
+        self.max_player_bullets.hash(state);
+        self.fire_cooldown.hash(state);
+        self.respawn_ticks.hash(state);
+        self.bullet_collisions.hash(state);
+        self.bullet_collision_score.hash(state);
+        self.mystery_interval.hash(state);
+        self.max_waves.hash(state);
+    }
+}
+
+/// The probability, that an alien of each type shoots in one step
+///
+/// There is no probability for the mystery ship, since it never shoots.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ShootProbability {
+    pub hard: f64,
+    pub medium: f64,
+    pub easy: f64,
+}
+
+impl ShootProbability {
+    pub fn get(&self, alien_type: AlienType) -> f64 {
+        match alien_type {
+            AlienType::Mystery => 0.,
+            AlienType::Hard => self.hard,
+            AlienType::Medium => self.medium,
+            AlienType::Easy => self.easy,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    #[cfg(feature = "json")]
+    Json(serde_json::Error),
+    #[cfg(feature = "toml")]
+    Toml(toml::de::Error),
+    /// The config describes a game, that can't be played
+    Invalid(&'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "json")]
+            Self::Json(err) => write!(f, "invalid json config: {}", err),
+            #[cfg(feature = "toml")]
+            Self::Toml(err) => write!(f, "invalid toml config: {}", err),
+            Self::Invalid(reason) => write!(f, "invalid game config: {}", reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid(change: impl FnOnce(&mut GameConfig)) -> bool {
+        let mut config = GameConfig::default();
+        change(&mut config);
+        matches!(config.validate(), Err(ConfigError::Invalid(_)))
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert!(GameConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn unplayable_configs_are_invalid() {
+        assert!(invalid(|config| config.alien_rows = 0));
+        assert!(invalid(|config| config.alien_columns = 0));
+        assert!(invalid(|config| config.alien_columns = 20));
+        assert!(invalid(|config| config.alien_rows = 20));
+        assert!(invalid(|config| config.lives = 0));
+        assert!(invalid(|config| config.bunkers = 20));
+        assert!(invalid(|config| config.bunker_erosion = 0));
+        assert!(invalid(|config| config.shoot_probability.hard = 1.5));
+        assert!(invalid(|config| config.shoot_probability.easy = -0.1));
+        assert!(invalid(|config| config.shoot_probability.medium = f64::NAN));
+        assert!(invalid(|config| config.cannon_speed = 0));
+        assert!(invalid(|config| config.max_player_bullets = Some(0)));
+        assert!(invalid(|config| config.max_waves = Some(0)));
+    }
+
+    #[test]
+    fn rule_variants_are_valid() {
+        assert!(!invalid(|config| config.lives = 1));
+        assert!(!invalid(|config| config.bunkers = 0));
+        assert!(!invalid(|config| config.max_player_bullets = None));
+        assert!(!invalid(|config| config.shoot_probability.hard = 1.));
+        assert!(!invalid(|config| config.max_waves = Some(1)));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_config_falls_back_to_the_defaults() {
+        let config = GameConfig::from_json(r#"{
+            "lives": 1,
+            "max_player_bullets": null,
+            "shoot_probability": { "hard": 0.5, "medium": 0.25, "easy": 0 }
+        }"#).unwrap();
+
+        assert_eq!(config, GameConfig {
+            lives: 1,
+            max_player_bullets: None,
+            shoot_probability: ShootProbability { hard: 0.5, medium: 0.25, easy: 0. },
+            ..GameConfig::default()
+        });
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_config_is_parsed_and_validated() {
+        assert!(matches!(GameConfig::from_json(r#"{ "lives": 0 }"#), Err(ConfigError::Invalid(_))));
+        assert!(matches!(GameConfig::from_json(r#"{ "lives": -1 }"#), Err(ConfigError::Json(_))));
+        assert!(matches!(GameConfig::from_json("lives = 1"), Err(ConfigError::Json(_))));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_config_falls_back_to_the_defaults() {
+        let config = GameConfig::from_toml("
+            bunkers = 0
+            max_waves = 3
+
+            [shoot_probability]
+            hard = 0.5
+            medium = 0.25
+            easy = 0.0
+        ").unwrap();
+
+        assert_eq!(config, GameConfig {
+            bunkers: 0,
+            max_waves: Some(3),
+            shoot_probability: ShootProbability { hard: 0.5, medium: 0.25, easy: 0. },
+            ..GameConfig::default()
+        });
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_config_is_parsed_and_validated() {
+        assert!(matches!(GameConfig::from_toml("cannon_speed = 0"), Err(ConfigError::Invalid(_))));
+        assert!(matches!(GameConfig::from_toml("cannon_speed = \"fast\""), Err(ConfigError::Toml(_))));
+        assert!(matches!(GameConfig::from_toml(r#"{ "lives": 1 }"#), Err(ConfigError::Toml(_))));
+    }
+}
@@ -0,0 +1,109 @@
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{GameObj, GameStatus, Input, PlayField, Position, Score};
+use crate::alien::AlienType;
+use crate::bullet::BulletDirection;
+use crate::event::Event;
+use crate::features::Features;
+use crate::reward::StepTally;
+
+pub use game::{ActionSpace, Environment, Reward};
+
+/// Everything an agent can see of a [`PlayField`]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Observation {
+    pub cannon: Position,
+    pub aliens: Vec<(AlienType, Position)>,
+    pub mystery: Option<Position>,
+    pub bullets: Vec<(Position, BulletDirection)>,
+    pub bunkers: Vec<Position>,
+    pub lives: usize,
+    pub wave: usize,
+    /// Whether the cannon fires, if it is told to shoot in the next step
+    pub can_fire: bool,
+    /// The number of steps the game stays frozen, until the cannon respawns
+    pub respawn_ticks_left: usize,
+    /// The same state encoded as a fixed-length feature vector
+    pub features: Features,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Info {
+    pub status: GameStatus,
+    pub score: Score,
+    /// Everything, that happened during the step
+    pub tally: StepTally,
+    /// The events of the step, in the order they happened
+    pub events: Vec<Event>,
+}
+
+impl Info {
+    /// Appends the info of the following step
+    pub(crate) fn extend(&mut self, next: Info) {
+        self.status = next.status;
+        self.score = next.score;
+        self.tally += next.tally;
+        self.events.extend(next.events);
+    }
+}
+
+impl PlayField {
+    /// Advances the game like [`Environment::step`], without building an observation
+    pub(crate) fn step_unobserved(&mut self, action: Input) -> (Reward, Info) {
+        let status = self.step(action.instruction, action.shoot);
+        let info = Info {
+            status,
+            score: self.score(),
+            tally: self.last_step,
+            events: self.drain_events().collect(),
+        };
+
+        (self.reward_config.reward(&self.last_step), info)
+    }
+
+    pub fn observe(&self) -> Observation {
+        Observation {
+            cannon: self.cannon().position(),
+            aliens: self.world.observe_aliens(),
+            mystery: self.mystery().map(|mystery| mystery.position()),
+            bullets: self.world.observe_bullets(),
+            bunkers: self.world.observe_bunkers(),
+            lives: self.lives(),
+            wave: self.wave(),
+            can_fire: self.can_fire(),
+            respawn_ticks_left: self.respawn_ticks_left(),
+            features: self.features(),
+        }
+    }
+}
+
+/// The actions are the [`Input`]s, the reward is computed by the
+/// [reward config](PlayField::set_reward_config)
+impl Environment for PlayField {
+    type Observation = Observation;
+    type Action = Input;
+    type Info = Info;
+
+    fn reset(&mut self, seed: u64) -> Self::Observation {
+        let reward_config = self.reward_config;
+        *self = PlayField::with_seed_and_config(seed, *self.config());
+        self.reward_config = reward_config;
+
+        self.observe()
+    }
+
+    fn step(&mut self, action: Self::Action) -> (Self::Observation, Reward, bool, Self::Info) {
+        let (reward, info) = self.step_unobserved(action);
+
+        (self.observe(), reward, info.status.is_done(), info)
+    }
+
+    fn action_space(&self) -> ActionSpace {
+        ActionSpace::Discrete(Input::COUNT)
+    }
+}
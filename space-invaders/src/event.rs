@@ -0,0 +1,163 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Player, Position, Score};
+use crate::alien::AlienType;
+use crate::reward::StepTally;
+
+/// Something, that happened during a [`PlayField::step`](crate::PlayField::step)
+///
+/// Events are collected by the [`PlayField`](crate::PlayField), until they are drained with
+/// [`PlayField::drain_events`](crate::PlayField::drain_events).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Event {
+    /// The cannon of the player fired a bullet
+    ShotFired { player: Player, position: Position },
+    /// A bullet left the field without hitting anything
+    ///
+    /// The player is `None` for alien bullets.
+    BulletExpired { player: Option<Player>, position: Position },
+    /// A bullet of the player and an alien bullet destroyed each other
+    BulletShotDown { player: Player, position: Position },
+    AlienKilled { player: Player, alien_type: AlienType, position: Position, points: Score },
+    /// A bullet chewed into a bunker at the given position
+    ///
+    /// The player is `None` for alien bullets.
+    BunkerHit { player: Option<Player>, position: Position },
+    CannonHit { player: Player, position: Position, lives_left: usize },
+    /// The given wave was cleared
+    WaveCleared { wave: usize },
+    MysterySpawned { position: Position },
+}
+
+impl StepTally {
+    /// Counts the event
+    ///
+    /// The score, the bunker damage and the survived steps are not part of any event, so they
+    /// have to be tallied separately.
+    pub fn record(&mut self, event: &Event) {
+        match *event {
+            Event::ShotFired { .. } => self.shots_fired += 1,
+            Event::BulletExpired { player, .. } => if player.is_some() {
+                self.misses += 1;
+            },
+            Event::BulletShotDown { .. } => self.bullets_shot_down += 1,
+            Event::AlienKilled { alien_type, .. } => *self.kills.get_mut(alien_type) += 1,
+            Event::CannonHit { .. } => self.lives_lost += 1,
+            Event::WaveCleared { .. } => self.waves_cleared += 1,
+            Event::BunkerHit { .. } | Event::MysterySpawned { .. } => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use crate::{Bullet, GameObj, Instruction, PlayField, Player, Position};
+    use crate::alien::Alien;
+    use crate::bunker::Bunker;
+    use crate::config::GameConfig;
+
+    use super::*;
+
+    fn step(play_field: &mut PlayField, shoot: bool) -> Vec<Event> {
+        play_field.step(Instruction::None, shoot);
+        play_field.drain_events().collect()
+    }
+
+    #[test]
+    fn firing_is_an_event() {
+        let mut play_field = PlayField::with_seed(0);
+        let events = step(&mut play_field, true);
+
+        assert!(events.iter().any(|event| matches!(event, Event::ShotFired { player: Player::One, .. })));
+    }
+
+    #[test]
+    fn bullets_leaving_the_field_expire() {
+        let mut play_field = PlayField::with_seed(0);
+        let position = Position { x: PlayField::WIDTH - 1, y: 0 };
+        play_field.world.bullets = vec![Bullet::player_at_position(position, Player::One)];
+
+        let events = step(&mut play_field, false);
+        assert!(events.contains(&Event::BulletExpired { player: Some(Player::One), position }));
+    }
+
+    #[test]
+    fn killed_aliens_are_events_with_their_points() {
+        let mut play_field = PlayField::with_seed(0);
+        let alien = play_field.world.aliens.shooters().next().unwrap().clone();
+        let position = alien.position();
+        play_field.world.bullets = vec![Bullet::player_at_position(
+            Position { x: position.x + Alien::WIDTH / 2, y: position.y + Alien::HEIGHT },
+            Player::One,
+        )];
+
+        let events = step(&mut play_field, false);
+        assert!(events.contains(&Event::AlienKilled {
+            player: Player::One,
+            alien_type: alien.alien_type(),
+            position,
+            points: 10,
+        }));
+        assert_eq!(play_field.score(), 10);
+    }
+
+    #[test]
+    fn bunker_hits_are_events() {
+        let mut play_field = PlayField::with_seed(0);
+        let bunker = play_field.bunkers().iter().flatten().next().unwrap().position();
+        play_field.world.bullets = vec![Bullet::alien_at_position(
+            Position { x: bunker.x + Bunker::WIDTH / 2, y: bunker.y - 2 },
+            AlienType::Easy,
+        )];
+
+        let events = step(&mut play_field, false);
+        assert!(events.iter().any(|event| matches!(event, Event::BunkerHit { player: None, .. })));
+    }
+
+    #[test]
+    fn cleared_waves_are_events() {
+        let mut play_field = PlayField::with_seed_and_config(0, GameConfig {
+            alien_rows: 1,
+            alien_columns: 1,
+            ..GameConfig::default()
+        });
+        let position = play_field.world.aliens.position();
+        play_field.world.bullets = vec![Bullet::player_at_position(
+            Position { x: position.x + Alien::WIDTH / 2, y: position.y + Alien::HEIGHT },
+            Player::One,
+        )];
+
+        let events = step(&mut play_field, false);
+        assert!(events.iter().any(|event| matches!(event, Event::AlienKilled { alien_type: AlienType::Hard, .. })));
+        assert_eq!(events.last(), Some(&Event::WaveCleared { wave: 1 }));
+    }
+
+    #[test]
+    fn spawned_mystery_ships_are_events() {
+        let mut play_field = PlayField::with_seed_and_config(0, GameConfig {
+            mystery_interval: 1,
+            ..GameConfig::default()
+        });
+
+        let events = step(&mut play_field, false);
+        assert!(events.contains(&Event::MysterySpawned { position: Alien::mystery().position() }));
+    }
+
+    #[test]
+    fn draining_empties_the_events() {
+        let mut play_field = PlayField::with_seed(0);
+        play_field.step(Instruction::None, true);
+        play_field.step(Instruction::MoveLeft, false);
+
+        let events = play_field.events().to_vec();
+        assert!(!events.is_empty());
+        assert_eq!(play_field.drain_events().collect::<Vec<_>>(), events);
+        assert!(play_field.events().is_empty());
+        assert_eq!(play_field.drain_events().count(), 0);
+    }
+}
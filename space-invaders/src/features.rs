@@ -0,0 +1,197 @@
+//! Fixed-length, normalized feature vectors for agents
+//!
+//! The layout of a [`Features`] vector (version [`SCHEMA_VERSION`]) is:
+//!
+//! | offset | length                   | feature                                                   |
+//! |--------|--------------------------|-----------------------------------------------------------|
+//! | 0      | 1                        | x of the cannon center / `PlayField::WIDTH`               |
+//! | 1      | 4 * [`NEAREST_BULLETS`]  | the bullets nearest to the cannon, see below              |
+//! | 33     | 3 * `alien_columns`      | the lowest alien of each column, see below                |
+//! | 66     | 2                        | the mystery ship: present (0/1), x / `PlayField::WIDTH`   |
+//! | 68     | 9 * `bunkers`            | the health of the 3x3 cells of each bunker, row by row    |
+//! | 104    | 1                        | lives / initial lives                                     |
+//! | 105    | 1                        | (wave - 1) / wave                                         |
+//! | 106    | 1                        | whether the cannon can fire (0/1)                         |
+//!
+//! `alien_columns`, `bunkers` and the initial lives are part of the
+//! [`GameConfig`](crate::config::GameConfig), the offsets are the ones of the default config.
+//!
+//! Every bullet is encoded as `[present (0/1), dx, dy, direction]`, where `dx` and `dy` are the
+//! distances to the cannon center divided by the size of the `PlayField` (so in `-1..=1`) and
+//! `direction` is `1` for alien bullets (moving down) and `-1` for player bullets (moving up).
+//! Bullets are sorted by their distance to the cannon; missing bullets are all zeros.
+//!
+//! Every column is encoded as `[present (0/1), x, y]`, where `x` and `y` are the center of the
+//! lowest surviving alien divided by the size of the `PlayField`.
+//!
+//! Every other value is in `0..=1`.
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{GameObj, PlayField, Position};
+use crate::alien::Alien;
+use crate::bullet::BulletDirection;
+use crate::cannon::Cannon;
+use crate::config::GameConfig;
+
+/// The version of the feature layout
+///
+/// It stays at 1 until the layout is first released, after that it is increased with every
+/// change to the layout, so agents can detect, that they were trained on a different layout.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The number of bullets encoded in a feature vector
+pub const NEAREST_BULLETS: usize = 8;
+
+const BULLET_FEATURES: usize = 4;
+const COLUMN_FEATURES: usize = 3;
+const BUNKER_FEATURES: usize = 9;
+
+/// A feature vector of [`Features::len`] values
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Features {
+    // stored, so serialized vectors keep the version of the layout they were encoded with
+    version: u32,
+    values: Vec<f32>,
+}
+
+impl Features {
+    /// The length of the feature vectors of games with the given rules
+    pub fn len(config: &GameConfig) -> usize {
+        1
+            + NEAREST_BULLETS * BULLET_FEATURES
+            + config.alien_columns * COLUMN_FEATURES
+            + 2
+            + config.bunkers * BUNKER_FEATURES
+            + 3
+    }
+
+    /// The version of the layout, this vector was encoded with
+    pub fn schema_version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        &self.values
+    }
+
+    pub fn into_vec(self) -> Vec<f32> {
+        self.values
+    }
+}
+
+impl PlayField {
+    /// Encodes the current state as a feature vector
+    pub fn features(&self) -> Features {
+        let mut values = Vec::with_capacity(Features::len(self.config()));
+        let cannon = Position {
+            x: self.cannon().position().x + Cannon::WIDTH / 2,
+            y: self.cannon().position().y,
+        };
+
+        values.push(x(cannon.x));
+
+        let mut bullets = self.bullets().iter().collect::<Vec<_>>();
+        bullets.sort_by_key(|bullet| {
+            let dx = bullet.position().x as i64 - cannon.x as i64;
+            let dy = bullet.position().y as i64 - cannon.y as i64;
+            dx * dx + dy * dy
+        });
+        for i in 0..NEAREST_BULLETS {
+            match bullets.get(i) {
+                Some(bullet) => values.extend_from_slice(&[
+                    1.,
+                    (bullet.position().x as f32 - cannon.x as f32) / PlayField::WIDTH as f32,
+                    (bullet.position().y as f32 - cannon.y as f32) / PlayField::HEIGHT as f32,
+                    match bullet.direction() {
+                        BulletDirection::Downwards => 1.,
+                        BulletDirection::Upwards => -1.,
+                    },
+                ]),
+                None => values.extend_from_slice(&[0.; BULLET_FEATURES]),
+            }
+        }
+
+        for col in self.aliens().iter() {
+            match col.iter().rev().find_map(|alien| alien.as_ref()) {
+                Some(alien) => values.extend_from_slice(&[
+                    1.,
+                    x(alien.position().x + Alien::WIDTH / 2),
+                    y(alien.position().y + Alien::HEIGHT / 2),
+                ]),
+                None => values.extend_from_slice(&[0.; COLUMN_FEATURES]),
+            }
+        }
+
+        match self.mystery() {
+            Some(mystery) => values.extend_from_slice(&[1., x(mystery.position().x + Alien::WIDTH / 2)]),
+            None => values.extend_from_slice(&[0., 0.]),
+        }
+
+        for bunker in self.bunkers().iter() {
+            match bunker {
+                Some(bunker) => bunker
+                    .health()
+                    .iter()
+                    .for_each(|row| values.extend_from_slice(row)),
+                None => values.extend_from_slice(&[0.; BUNKER_FEATURES]),
+            }
+        }
+
+        values.push(self.lives() as f32 / self.config().lives as f32);
+        values.push((self.wave() - 1) as f32 / self.wave() as f32);
+        values.push(self.can_fire() as u8 as f32);
+
+        debug_assert_eq!(values.len(), Features::len(self.config()));
+        Features {
+            version: SCHEMA_VERSION,
+            values,
+        }
+    }
+}
+
+fn x(x: usize) -> f32 {
+    (x as f32 / PlayField::WIDTH as f32).min(1.)
+}
+
+fn y(y: usize) -> f32 {
+    (y as f32 / PlayField::HEIGHT as f32).min(1.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_layout_matches_the_documented_offsets() {
+        let mut game = PlayField::with_seed(0);
+        game.world.mystery = Some(Alien::mystery());
+        let features = game.features();
+        let values = features.as_slice();
+
+        assert_eq!(features.schema_version(), SCHEMA_VERSION);
+        assert_eq!(Features::len(game.config()), 107);
+        assert_eq!(values.len(), 107);
+
+        // no bullets yet
+        assert!(values[1..33].iter().all(|&value| value == 0.));
+
+        let lowest = game.aliens().iter().next().unwrap().last().unwrap().as_ref().unwrap();
+        assert_eq!(values[33..36], [
+            1.,
+            x(lowest.position().x + Alien::WIDTH / 2),
+            y(lowest.position().y + Alien::HEIGHT / 2),
+        ]);
+
+        assert_eq!(values[66..68], [1., x(Alien::WIDTH / 2)]);
+
+        let bunker = game.bunkers().iter().next().unwrap().as_ref().unwrap();
+        assert_eq!(values[68..71], bunker.health()[0]);
+
+        assert_eq!(values[104..], [1., 0., 1.]);
+    }
+}
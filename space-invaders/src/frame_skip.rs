@@ -0,0 +1,247 @@
+//! Repeating an action over multiple steps
+//!
+//! Agents rarely need to decide every single step. Repeating each action for a few steps
+//! shortens episodes by the same factor, which speeds up training considerably.
+
+use crate::{Input, PlayField};
+use crate::env::{ActionSpace, Environment, Info, Observation, Reward};
+use crate::raster::{ColorMode, Frame};
+
+impl PlayField {
+    /// Repeats `action` for `n` steps, or until the game is done
+    ///
+    /// Returns the final observation, the summed reward, and the info of all steps combined.
+    /// At least one step is taken, even if `n` is `0`.
+    pub fn step_n(&mut self, action: Input, n: usize) -> (Observation, Reward, bool, Info) {
+        let (reward, info) = self.repeat(action, n);
+
+        (self.observe(), reward, info.status.is_done(), info)
+    }
+
+    fn repeat(&mut self, action: Input, n: usize) -> (Reward, Info) {
+        let (mut reward, mut info) = self.step_unobserved(action);
+
+        for _ in 1..n {
+            if info.status.is_done() { break; }
+
+            let (next_reward, next_info) = self.step_unobserved(action);
+            reward += next_reward;
+            info.extend(next_info);
+        }
+
+        (reward, info)
+    }
+}
+
+/// An [`Environment`], that repeats every action for a fixed number of steps
+#[derive(Clone, Debug)]
+pub struct FrameSkip {
+    play_field: PlayField,
+    skip: usize,
+}
+
+impl FrameSkip {
+    /// Repeats every action `skip` times
+    pub fn new(play_field: PlayField, skip: usize) -> Self {
+        Self {
+            play_field,
+            skip: skip.max(1),
+        }
+    }
+
+    pub fn play_field(&self) -> &PlayField {
+        &self.play_field
+    }
+
+    pub fn into_inner(self) -> PlayField {
+        self.play_field
+    }
+}
+
+impl Environment for FrameSkip {
+    type Observation = Observation;
+    type Action = Input;
+    type Info = Info;
+
+    fn reset(&mut self, seed: u64) -> Self::Observation {
+        self.play_field.reset(seed)
+    }
+
+    fn step(&mut self, action: Self::Action) -> (Self::Observation, Reward, bool, Self::Info) {
+        self.play_field.step_n(action, self.skip)
+    }
+
+    fn action_space(&self) -> ActionSpace {
+        self.play_field.action_space()
+    }
+}
+
+/// An [`Environment`], that repeats every action for a fixed number of steps, and observes the
+/// rendered [`Frame`]s
+///
+/// With max-pooling, the observation is the pixel-wise maximum of the last two frames, like in
+/// the usual Atari setup.
+#[derive(Clone, Debug)]
+pub struct PixelFrameSkip {
+    play_field: PlayField,
+    skip: usize,
+    color_mode: ColorMode,
+    max_pool: bool,
+}
+
+impl PixelFrameSkip {
+    /// Repeats every action `skip` times, and renders the frames in `color_mode`
+    pub fn new(play_field: PlayField, skip: usize, color_mode: ColorMode) -> Self {
+        Self {
+            play_field,
+            skip: skip.max(1),
+            color_mode,
+            max_pool: false,
+        }
+    }
+
+    /// Observes the maximum of the last two frames, instead of only the last one
+    pub fn max_pool(mut self, max_pool: bool) -> Self {
+        self.max_pool = max_pool;
+        self
+    }
+
+    pub fn play_field(&self) -> &PlayField {
+        &self.play_field
+    }
+
+    pub fn into_inner(self) -> PlayField {
+        self.play_field
+    }
+
+    fn render(&self) -> Frame {
+        Frame::render(&self.play_field, self.color_mode)
+    }
+}
+
+impl Environment for PixelFrameSkip {
+    type Observation = Frame;
+    type Action = Input;
+    type Info = Info;
+
+    fn reset(&mut self, seed: u64) -> Self::Observation {
+        self.play_field.reset(seed);
+        self.render()
+    }
+
+    fn step(&mut self, action: Self::Action) -> (Self::Observation, Reward, bool, Self::Info) {
+        if !self.max_pool || self.skip == 1 {
+            let (reward, info) = self.play_field.repeat(action, self.skip);
+            return (self.render(), reward, info.status.is_done(), info);
+        }
+
+        // only the last two frames are rendered
+        let (mut reward, mut info) = self.play_field.repeat(action, self.skip - 1);
+        let frame = match info.status.is_done() {
+            true => self.render(),
+            false => {
+                let second_last = self.render();
+                let (next_reward, next_info) = self.play_field.step_unobserved(action);
+                reward += next_reward;
+                info.extend(next_info);
+                second_last.max(&self.render())
+            }
+        };
+
+        (frame, reward, info.status.is_done(), info)
+    }
+
+    fn action_space(&self) -> ActionSpace {
+        self.play_field.action_space()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use crate::{Bullet, GameObj, GameStatus, Instruction, Player, Position};
+    use crate::alien::Alien;
+    use crate::config::GameConfig;
+    use crate::event::Event;
+    use crate::reward::{RewardConfig, StepTally};
+
+    use super::*;
+
+    const RIGHT: Input = Input { instruction: Instruction::MoveRight, shoot: true };
+
+    #[test]
+    fn step_n_sums_rewards_and_tallies() {
+        let reward_config = RewardConfig {
+            step_survived: 1.,
+            shot_fired: 0.5,
+            ..RewardConfig::default()
+        };
+        let mut skipping = PlayField::with_seed(5);
+        skipping.set_reward_config(reward_config);
+        let mut single = skipping.clone();
+
+        let (_, reward, done, info) = skipping.step_n(RIGHT, 4);
+
+        let mut rewards = 0.;
+        let mut tally = StepTally::default();
+        let mut events = Vec::new();
+        for _ in 0..4 {
+            let (reward, info) = single.step_unobserved(RIGHT);
+            rewards += reward;
+            tally += info.tally;
+            events.extend(info.events);
+        }
+
+        assert!(!done);
+        assert_eq!(reward, rewards);
+        assert_eq!(info.tally, tally);
+        assert_eq!(info.tally.steps_survived, 4);
+        assert_eq!(info.events, events);
+        assert_eq!(skipping.checksum(), single.checksum());
+    }
+
+    #[test]
+    fn step_n_stops_when_the_game_is_done() {
+        let mut play_field = PlayField::with_seed_and_config(0, GameConfig {
+            alien_rows: 1,
+            alien_columns: 1,
+            max_waves: Some(1),
+            ..GameConfig::default()
+        });
+        let alien = play_field.aliens().position();
+        play_field.world.bullets = vec![Bullet::player_at_position(
+            Position { x: alien.x + Alien::WIDTH / 2, y: alien.y + Alien::HEIGHT },
+            Player::One,
+        )];
+        let mut single = play_field.clone();
+
+        let (_, _, done, info) = play_field.step_n(Input::from_index(0).unwrap(), 10);
+        single.step_unobserved(Input::from_index(0).unwrap());
+
+        assert!(done);
+        assert_eq!(info.status, GameStatus::Won);
+        assert_eq!(info.events.last(), Some(&Event::WaveCleared { wave: 1 }));
+        assert_eq!(play_field.checksum(), single.checksum());
+    }
+
+    #[test]
+    fn pixel_frame_skip_max_pools_the_last_two_frames() {
+        let play_field = PlayField::with_seed(0);
+        let mut single = play_field.clone();
+        let mut env = PixelFrameSkip::new(play_field, 3, ColorMode::Grayscale).max_pool(true);
+
+        let (frame, _, _, _) = env.step(RIGHT);
+
+        let frames = (0..3)
+            .map(|_| {
+                single.step_unobserved(RIGHT);
+                Frame::render(&single, ColorMode::Grayscale)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(frame, frames[1].max(&frames[2]));
+        assert_ne!(frame, frames[2]);
+        assert_ne!(frame, frames[0].max(&frames[1]).max(&frames[2]));
+    }
+}
@@ -3,83 +3,59 @@
 
 extern crate alloc;
 
-use alloc::vec::Vec;
+use alloc::vec::{Drain, Vec};
+use core::hash::{Hash, Hasher};
 
-use crate::alien::Aliens;
-use crate::bullet::{Bullet, Shot};
+use game::{Canvas, Game};
+pub use game::{GameObj, GameRng, Position, Score, Step, StepResult, Unit};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::alien::{Alien, Aliens};
+use crate::bullet::Bullet;
 use crate::bunker::Bunkers;
 use crate::cannon::Cannon;
+use crate::config::GameConfig;
+use crate::env::Observation;
+use crate::event::Event;
+use crate::replay::Fnv1a;
+use crate::reward::{RewardConfig, StepTally};
+use crate::world::{Contender, World};
 
 pub mod alien;
 pub mod bullet;
 pub mod bunker;
 pub mod cannon;
+pub mod config;
+pub mod env;
+pub mod event;
+pub mod features;
+pub mod frame_skip;
+pub mod raster;
+pub mod replay;
+pub mod reward;
+pub mod split_screen;
+pub mod vec_env;
+pub mod versus;
+mod world;
 
-pub type Unit = usize;
-pub type Score = i64;
-pub type Survived = bool;
 pub type AbsorbedHit = bool;
 
-#[derive(Clone, Copy, Default, Debug)]
-pub struct Position {
-    pub x: Unit,
-    pub y: Unit,
-}
-
-impl Position {
-    pub const fn overlaps(
-        &self,
-        self_width: Unit,
-        self_height: Unit,
-        other: Position,
-        other_width: Unit,
-        other_height: Unit,
-    ) -> bool {
-        let overlaps_on_x = self.x <= other.x + other_width - 1 && self.x + self_width - 1 >= other.x;
-        let overlaps_on_y = self.y <= other.y + other_height - 1 && self.y + self_height - 1 >= other.y;
-
-        overlaps_on_x && overlaps_on_y
-    }
-}
-
-pub trait GameObj {
-    const WIDTH: Unit;
-    const HEIGHT: Unit;
-
-    fn position(&self) -> Position;
-
-    fn overlaps<O: GameObj>(&self, other: &O) -> bool {
-        self
-            .position()
-            .overlaps(
-                Self::WIDTH,
-                Self::HEIGHT,
-                other.position(),
-                O::WIDTH,
-                O::HEIGHT,
-            )
-    }
-}
-
-pub trait Step {
-    fn step(&mut self) -> StepResult;
-}
-
 pub trait WouldHit<T>
     where T: GetHit {
     fn would_hit(&mut self, bullet: &Bullet) -> Option<&mut T>;
 }
 
 pub trait GetHit {
-    fn hit(&mut self, _bullet: &Bullet, _score: &mut Score) -> HitResult {
+    fn hit(&mut self, _bullet: &Bullet, _score: &mut Score, _rng: &mut GameRng) -> HitResult {
         HitResult::default()
     }
 }
 
 impl<T: GetHit> GetHit for Option<T> {
-    fn hit(&mut self, bullet: &Bullet, score: &mut i64) -> HitResult {
+    fn hit(&mut self, bullet: &Bullet, score: &mut i64, rng: &mut GameRng) -> HitResult {
         let hr = match self {
-            Some(inner) => inner.hit(bullet, score),
+            Some(inner) => inner.hit(bullet, score, rng),
             None => HitResult { survived: true, absorbed_bullet: false }
         };
 
@@ -91,20 +67,6 @@ impl<T: GetHit> GetHit for Option<T> {
     }
 }
 
-pub struct StepResult {
-    survived: bool,
-    shot: Shot,
-}
-
-impl Default for StepResult {
-    fn default() -> Self {
-        Self {
-            survived: true,
-            shot: Shot::None,
-        }
-    }
-}
-
 pub struct HitResult {
     survived: bool,
     absorbed_bullet: bool,
@@ -119,125 +81,334 @@ impl Default for HitResult {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// The outcome of a single [`PlayField::step`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameStatus {
+    Running,
+    /// The cannon was hit, but there are lives left
+    ///
+    /// All bullets are cleared, the cannon is reset and the game freezes for
+    /// [`GameConfig::respawn_ticks`] steps.
+    LifeLost,
+    /// The game is frozen, while the cannon respawns
+    Respawning,
+    /// All aliens of the current wave were destroyed and the next wave spawned
+    WaveCleared,
+    GameOver { reason: GameOverReason },
+    /// All [`GameConfig::max_waves`] were cleared
+    Won,
+}
+
+impl GameStatus {
+    /// Whether the episode is done, i.e. the game is either over or won
+    pub fn is_done(&self) -> bool {
+        matches!(self, Self::GameOver { .. } | Self::Won)
+    }
+}
+
+impl game::Status for GameStatus {
+    fn is_done(&self) -> bool {
+        GameStatus::is_done(self)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameOverReason {
+    NoLivesLeft,
+    /// The aliens reached the row of the cannon
+    Invaded,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Instruction {
     MoveRight,
     MoveLeft,
     None,
 }
 
+/// One of the two players of a [`Versus`](versus::Versus) game
+///
+/// In a single player game, every bullet belongs to [`Player::One`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Player {
+    One,
+    Two,
+}
+
+impl Player {
+    pub const BOTH: [Player; 2] = [Player::One, Player::Two];
+
+    /// `0` for [`Player::One`], `1` for [`Player::Two`]
+    pub fn index(&self) -> usize {
+        match self {
+            Self::One => 0,
+            Self::Two => 1,
+        }
+    }
+
+    pub fn opponent(&self) -> Self {
+        match self {
+            Self::One => Self::Two,
+            Self::Two => Self::One,
+        }
+    }
+}
+
+/// Everything a [`PlayField`] receives in one step
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Input {
+    pub instruction: Instruction,
+    pub shoot: bool,
+}
+
+impl Input {
+    /// The number of distinct inputs
+    pub const COUNT: usize = 6;
+
+    /// Maps `0..Input::COUNT` to all distinct inputs
+    pub fn from_index(index: usize) -> Option<Self> {
+        let instruction = match index % 3 {
+            0 => Instruction::None,
+            1 => Instruction::MoveLeft,
+            _ => Instruction::MoveRight,
+        };
+
+        (index < Self::COUNT).then_some(Self {
+            instruction,
+            shoot: index >= 3,
+        })
+    }
+
+    /// The inverse of [`Input::from_index`]
+    pub fn index(&self) -> usize {
+        let instruction = match self.instruction {
+            Instruction::None => 0,
+            Instruction::MoveLeft => 1,
+            Instruction::MoveRight => 2,
+        };
+
+        instruction + self.shoot as usize * 3
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PlayField {
-    aliens: Aliens,
-    bunkers: Bunkers,
-    bullets: Vec<Bullet>,
-    cannon: Cannon,
+    world: World,
+    player: Contender,
+    status: GameStatus,
+
+    last_step: StepTally,
+    reward_config: RewardConfig,
+}
 
-    score: Score,
-    lives: usize,
-    speed: Unit,
+/// A game with the default rules and a random seed
+impl Default for PlayField {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PlayField {
     pub const HEIGHT: Unit = 256;
     pub const WIDTH: Unit = 224;
-    pub const PLAYER_LIVES: usize = 3;
 
+    /// Creates a game with the default rules and a random seed
     pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    /// Creates a game with the given rules and a random seed
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the config is [invalid](GameConfig::validate).
+    pub fn new_with_config(config: GameConfig) -> Self {
+        Self::with_seed_and_config(rand::random(), config)
+    }
+
+    /// Creates a game with the default rules, whose randomness is fully determined by `seed`
+    ///
+    /// Two games with the same seed, that receive the same instructions, are identical.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_seed_and_config(seed, GameConfig::default())
+    }
+
+    /// Creates a game with the given rules, whose randomness is fully determined by `seed`
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the config is [invalid](GameConfig::validate).
+    pub fn with_seed_and_config(seed: u64, config: GameConfig) -> Self {
         Self {
-            aliens: Aliens::new(),
-            bunkers: Bunkers::new(),
-            bullets: Vec::new(),
-            cannon: Cannon::new(),
-            score: 0,
-            lives: Self::PLAYER_LIVES,
-            speed: 1,
+            world: World::new(seed, config),
+            player: Contender::new(Cannon::new(), config.lives),
+            status: GameStatus::Running,
+            last_step: StepTally::default(),
+            reward_config: RewardConfig::default(),
         }
     }
 
+    /// Captures the whole state of the game, including the state of the random number generator
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            play_field: self.clone(),
+        }
+    }
+
+    /// Resets the game to the state captured in `snapshot`
+    ///
+    /// Since the random number generator is restored as well, the game continues exactly like
+    /// it did after the snapshot was taken.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        *self = snapshot.play_field.clone();
+    }
+
+    /// A hash of the whole game state, that is identical on every platform
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+
+        self.world.hash(&mut hasher);
+        self.player.hash(&mut hasher);
+        self.status.hash(&mut hasher);
+        // the tally of the last step and the reward config don't influence the game
+
+        hasher.finish()
+    }
+
     pub fn aliens(&self) -> &Aliens {
-        &self.aliens
+        &self.world.aliens
+    }
+
+    pub fn mystery(&self) -> Option<&Alien> {
+        self.world.mystery.as_ref()
     }
 
     pub fn bunkers(&self) -> &Bunkers {
-        &self.bunkers
+        &self.world.bunkers
     }
 
-    pub fn bullets(&self) -> &Vec<Bullet> { &self.bullets }
+    pub fn bullets(&self) -> &Vec<Bullet> { &self.world.bullets }
 
     pub fn cannon(&self) -> &Cannon {
-        &self.cannon
+        &self.player.cannon
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.world.seed
     }
 
     pub fn score(&self) -> i64 {
-        self.score
+        self.player.score
     }
 
     pub fn lives(&self) -> usize {
-        self.lives
+        self.player.lives
     }
 
-    pub fn step(&mut self, instruction: Instruction, shoot: bool) -> Survived {
-        match instruction {
-            Instruction::MoveRight => self.cannon.move_right(),
-            Instruction::MoveLeft => self.cannon.move_left(),
-            Instruction::None => {}
-        }
+    /// The current wave, starting at 1
+    pub fn wave(&self) -> usize {
+        self.world.wave
+    }
+
+    /// The rules of the game
+    pub fn config(&self) -> &GameConfig {
+        &self.world.config
+    }
+
+    /// The events, that happened since the events were drained the last time
+    pub fn events(&self) -> &[Event] {
+        &self.world.events
+    }
+
+    /// Removes and returns all collected events
+    ///
+    /// Events accumulate over all steps, until they are drained.
+    pub fn drain_events(&mut self) -> Drain<'_, Event> {
+        self.world.events.drain(..)
+    }
+
+    /// Everything, that happened during the last [`PlayField::step`]
+    pub fn last_step(&self) -> &StepTally {
+        &self.last_step
+    }
+
+    pub fn reward_config(&self) -> &RewardConfig {
+        &self.reward_config
+    }
+
+    /// Sets the weights for the reward of the [`Environment`](env::Environment)
+    pub fn set_reward_config(&mut self, reward_config: RewardConfig) {
+        self.reward_config = reward_config;
+    }
+
+    /// The number of steps the game stays frozen, until the cannon respawns
+    pub fn respawn_ticks_left(&self) -> usize {
+        self.player.respawn_ticks_left
+    }
+
+    /// Whether the cannon fires, if it is told to shoot in the next step
+    ///
+    /// A cannon, that respawns, can't fire. See also [`GameConfig::max_player_bullets`] and
+    /// [`GameConfig::fire_cooldown`].
+    pub fn can_fire(&self) -> bool {
+        self.player.is_active() && self.world.can_fire(&self.player, Player::One)
+    }
 
-        if shoot {
-            self.bullets.push(self.cannon.shoot());
+    /// The status returned by the last [`PlayField::step`]
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    /// Advances the game by one step
+    ///
+    /// Once the returned status [is done](GameStatus::is_done), the game does not change anymore
+    /// and every further step returns the same status.
+    pub fn step(&mut self, instruction: Instruction, shoot: bool) -> GameStatus {
+        self.last_step = StepTally::default();
+        if self.status.is_done() {
+            return self.status;
+        }
+        self.player.steps_survived += 1;
+        if self.player.respawn_ticks_left > 0 {
+            self.player.respawn_ticks_left -= 1;
+            self.last_step.steps_survived = 1;
+            self.status = GameStatus::Respawning;
+            return self.status;
         }
+        let score_before = self.player.score;
+        let bunker_pixels_before = self.world.bunkers.pixels();
+        let events_before = self.world.events.len();
+
+        self.world.control(&mut self.player, Player::One, Input { instruction, shoot });
+        let target = self.player.cannon.position().x + Cannon::WIDTH / 2;
+        let advance = self.world.advance(core::slice::from_mut(&mut self.player), Some(target), Some(Player::One));
+
+        // the whole game is reset and freezes, while the cannon respawns
+        let life_lost = advance.lives_lost[Player::One.index()];
+        if life_lost {
+            self.world.bullets.clear();
+            self.player.cannon = Cannon::new();
+            self.player.respawn_ticks_left = self.world.config.respawn_ticks;
+        } else {
+            self.world.bullets.extend(advance.alien_shots);
+        }
+
+        self.status = self.world.status(self.player.is_eliminated(), life_lost);
 
-        // todo: handle all aliens died
-        let aliens_sr = self.aliens.step();
-        let mut survived = true;
-        let score = &mut self.score;
-        let lives = &mut self.lives;
-        let cannon = &mut self.cannon;
-        let aliens = &mut self.aliens;
-        let bunkers = &mut self.bunkers;
-
-        self.bullets.drain_filter(|bullet| {
-            // bullet is out of field
-            if !bullet.step().survived || !Self::overlaps(bullet) {
-                log::info!("bullet: {:?}", bullet);
-                *score -= 1;
-                return true;
-            }
-
-            // bullet hit the Cannon
-            if let Some(_) = cannon.would_hit(bullet) {
-                log::info!("hit cannon | {}", lives);
-                *lives = lives.saturating_sub(1);
-                survived = false;
-                return true;
-            } else if bullet.position().x > cannon.position().x && bullet.position().y > cannon.position().y {
-                log::info!("bullet: {:?} | cannon: {:?}", bullet, cannon)
-            }
-
-            // bullet hit an Alien
-            if let Some(alien) = aliens.would_hit(bullet) {
-                if alien.hit(bullet, score).absorbed_bullet {
-                    return true;
-                }
-            }
-
-            // bullet hit Bunker
-            if let Some(bunker) = bunkers.would_hit(bullet) {
-                if bunker.hit(bullet, score).absorbed_bullet {
-                    return true;
-                }
-            }
-
-            false
-        });
-
-        match aliens_sr.shot {
-            Shot::One(bullet) => self.bullets.push(bullet),
-            Shot::Many(bullets) => self.bullets.extend(bullets),
-            Shot::None => {}
+        for event in &self.world.events[events_before..] {
+            self.last_step.record(event);
         }
+        self.last_step.score = self.player.score - score_before;
+        self.last_step.bunker_damage = bunker_pixels_before - self.world.bunkers.pixels();
+        self.last_step.steps_survived = !matches!(self.status, GameStatus::GameOver { .. }) as usize;
 
-        survived
+        self.status
     }
 
     pub fn overlaps<O: GameObj>(other: &O) -> bool {
@@ -251,3 +422,200 @@ impl PlayField {
             )
     }
 }
+
+/// A single player game, whose actions are [`Input`]s
+/// Since the trait has no access to the events, they are dropped at every step.
+impl Game for PlayField {
+    type Observation = Observation;
+    type Action = Input;
+    type Status = GameStatus;
+
+    const WIDTH: usize = PlayField::WIDTH;
+    const HEIGHT: usize = PlayField::HEIGHT;
+
+    fn step(&mut self, action: Self::Action) -> Self::Status {
+        self.world.events.clear();
+        PlayField::step(self, action.instruction, action.shoot)
+    }
+
+    fn status(&self) -> Self::Status {
+        self.status
+    }
+
+    fn observe(&self) -> Self::Observation {
+        PlayField::observe(self)
+    }
+
+    fn score(&self, player: usize) -> Score {
+        assert_eq!(player, 0, "there is only one player");
+        self.player.score
+    }
+
+    fn render(&self, canvas: &mut dyn Canvas) {
+        raster::draw(self, canvas);
+    }
+}
+
+/// The whole state of a [`PlayField`] at one point in time
+///
+/// See [`PlayField::snapshot`] and [`PlayField::restore`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Snapshot {
+    play_field: PlayField,
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use rand::RngCore;
+
+    use super::*;
+    use crate::alien::AlienType;
+    use crate::config::ShootProbability;
+
+    const STEPS: usize = 500;
+
+    // a deterministic, but busy, sequence of inputs
+    fn input(tick: usize) -> Input {
+        Input::from_index(tick / 3 % Input::COUNT).unwrap()
+    }
+
+    fn play(seed: u64, steps: usize) -> PlayField {
+        let mut play_field = PlayField::with_seed(seed);
+        for tick in 0..steps {
+            let Input { instruction, shoot } = input(tick);
+            play_field.step(instruction, shoot);
+        }
+
+        play_field
+    }
+
+    #[test]
+    fn same_seed_and_inputs_give_the_same_checksum() {
+        assert_eq!(play(3, STEPS).checksum(), play(3, STEPS).checksum());
+    }
+
+    #[test]
+    fn different_seeds_diverge() {
+        assert_ne!(play(3, STEPS).checksum(), play(4, STEPS).checksum());
+    }
+
+    #[test]
+    fn checksum_covers_the_random_number_generator() {
+        let play_field = play(3, STEPS);
+        let mut advanced = play_field.clone();
+        advanced.world.rng.next_u32();
+
+        assert_ne!(play_field.checksum(), advanced.checksum());
+    }
+
+    // a field, whose cannon is hit by two alien bullets in the next step
+    fn under_fire() -> PlayField {
+        let mut play_field = PlayField::with_seed(0);
+        let Position { x, y } = play_field.cannon().position();
+        play_field.world.bullets = vec![
+            Bullet::alien_at_position(Position { x: x + 2, y: y - 1 }, AlienType::Easy),
+            Bullet::alien_at_position(Position { x: x + 8, y: y - 1 }, AlienType::Medium),
+            Bullet::player_at_position(Position { x, y: 100 }, Player::One),
+        ];
+
+        play_field
+    }
+
+    #[test]
+    fn two_hits_in_one_step_cost_one_life() {
+        let mut play_field = under_fire();
+        let lives = play_field.lives();
+
+        assert_eq!(play_field.step(Instruction::None, false), GameStatus::LifeLost);
+        assert_eq!(play_field.lives(), lives - 1);
+        let hits = play_field
+            .events()
+            .iter()
+            .filter(|event| matches!(event, Event::CannonHit { .. }))
+            .count();
+        assert_eq!(hits, 1);
+    }
+
+    #[test]
+    fn lost_life_clears_the_bullets() {
+        let mut play_field = under_fire();
+        play_field.step(Instruction::None, false);
+
+        assert!(play_field.bullets().is_empty());
+        assert_eq!(play_field.cannon().position(), Cannon::new().position());
+    }
+
+    #[test]
+    fn respawn_freezes_the_game_for_respawn_ticks() {
+        let mut play_field = under_fire();
+        play_field.step(Instruction::None, false);
+        let aliens = play_field.aliens().position();
+
+        for _ in 0..play_field.config().respawn_ticks {
+            assert!(!play_field.can_fire());
+            assert!(!play_field.observe().can_fire);
+            assert_eq!(play_field.step(Instruction::MoveLeft, true), GameStatus::Respawning);
+            assert_eq!(play_field.aliens().position(), aliens);
+            assert_eq!(play_field.cannon().position(), Cannon::new().position());
+        }
+
+        assert_eq!(play_field.respawn_ticks_left(), 0);
+        assert!(play_field.can_fire());
+        assert!(play_field.observe().can_fire);
+        assert_ne!(play_field.step(Instruction::None, true), GameStatus::Respawning);
+        assert_eq!(play_field.bullets().iter().filter(|bullet| !bullet.is_alien_bullet()).count(), 1);
+    }
+
+    #[test]
+    fn marching_aliens_invade_a_passive_player() {
+        let config = GameConfig {
+            shoot_probability: ShootProbability { hard: 0., medium: 0., easy: 0. },
+            ..GameConfig::default()
+        };
+        let mut play_field = PlayField::with_seed_and_config(0, config);
+
+        let mut status = GameStatus::Running;
+        for _ in 0..100_000 {
+            status = play_field.step(Instruction::None, false);
+            if status.is_done() { break; }
+        }
+
+        assert_eq!(status, GameStatus::GameOver { reason: GameOverReason::Invaded });
+        assert_eq!(play_field.lives(), config.lives);
+    }
+
+    #[test]
+    fn restored_snapshot_continues_like_the_original() {
+        let mut original = play(3, STEPS);
+        let snapshot = original.snapshot();
+
+        let mut restored = PlayField::with_seed(4);
+        restored.restore(&snapshot);
+        for tick in STEPS..2 * STEPS {
+            let Input { instruction, shoot } = input(tick);
+            original.step(instruction, shoot);
+            restored.step(instruction, shoot);
+        }
+
+        assert_eq!(restored.checksum(), original.checksum());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialized_snapshot_continues_like_the_original() {
+        let json = serde_json::to_string(&play(3, STEPS).snapshot()).unwrap();
+        let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+
+        let mut restored = PlayField::with_seed(4);
+        restored.restore(&snapshot);
+        for tick in STEPS..2 * STEPS {
+            let Input { instruction, shoot } = input(tick);
+            restored.step(instruction, shoot);
+        }
+
+        assert_eq!(restored.checksum(), play(3, 2 * STEPS).checksum());
+    }
+}
@@ -0,0 +1,431 @@
+//! A headless rasterizer, that renders a [`PlayField`] into a pixel buffer
+//!
+//! The sprites are the same ones the frontend draws, so agents trained on rendered frames see
+//! exactly what a human player sees.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use game::Canvas;
+
+use crate::{GameObj, PlayField, Player, Position, Unit};
+use crate::alien::{Alien, AlienType};
+use crate::bullet::Bullet;
+use crate::bunker::Bunker;
+use crate::versus::Versus;
+use crate::world::World;
+
+pub use game::Color;
+
+/// A filled rectangle of a sprite, relative to the position of the game object
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub x: Unit,
+    pub y: Unit,
+    pub width: Unit,
+    pub height: Unit,
+}
+
+// hard aliens are four pixels smaller then easy aliens
+// space invaders centers them, so we do the same
+pub const ALIEN_HARD: &[Rect] = &[
+    Rect { x: 5, y: 0, width: 2, height: 1 },
+    Rect { x: 4, y: 1, width: 4, height: 1 },
+    Rect { x: 3, y: 2, width: 6, height: 1 },
+    Rect { x: 2, y: 3, width: 2, height: 2 },
+    Rect { x: 5, y: 3, width: 2, height: 4 },
+    Rect { x: 8, y: 3, width: 2, height: 2 },
+    Rect { x: 4, y: 4, width: 4, height: 1 },
+    Rect { x: 3, y: 5, width: 1, height: 1 },
+    Rect { x: 2, y: 6, width: 1, height: 1 },
+    Rect { x: 3, y: 7, width: 1, height: 1 },
+    Rect { x: 8, y: 5, width: 1, height: 1 },
+    Rect { x: 9, y: 6, width: 1, height: 1 },
+    Rect { x: 8, y: 7, width: 1, height: 1 },
+];
+
+// medium aliens are one pixel smaller then easy aliens
+// space invaders aligns them to the right, so we do the same
+pub const ALIEN_MEDIUM: &[Rect] = &[
+    Rect { x: 3, y: 0, width: 1, height: 1 },
+    Rect { x: 4, y: 1, width: 1, height: 1 },
+    Rect { x: 9, y: 0, width: 1, height: 1 },
+    Rect { x: 8, y: 1, width: 1, height: 1 },
+    Rect { x: 3, y: 2, width: 7, height: 1 },
+    Rect { x: 2, y: 3, width: 2, height: 1 },
+    Rect { x: 5, y: 3, width: 3, height: 1 },
+    Rect { x: 9, y: 3, width: 2, height: 1 },
+    Rect { x: 1, y: 4, width: 11, height: 2 },
+    Rect { x: 1, y: 6, width: 1, height: 1 },
+    Rect { x: 3, y: 6, width: 1, height: 1 },
+    Rect { x: 9, y: 6, width: 1, height: 1 },
+    Rect { x: 11, y: 6, width: 1, height: 1 },
+    Rect { x: 4, y: 7, width: 2, height: 1 },
+    Rect { x: 7, y: 7, width: 2, height: 1 },
+];
+
+pub const ALIEN_EASY: &[Rect] = &[
+    Rect { x: 4, y: 0, width: 4, height: 1 },
+    Rect { x: 1, y: 1, width: 10, height: 2 },
+    Rect { x: 0, y: 2, width: 3, height: 3 },
+    Rect { x: 5, y: 3, width: 2, height: 1 },
+    Rect { x: 9, y: 2, width: 3, height: 3 },
+    Rect { x: 3, y: 4, width: 6, height: 1 },
+    Rect { x: 2, y: 5, width: 3, height: 1 },
+    Rect { x: 7, y: 5, width: 3, height: 1 },
+    Rect { x: 1, y: 6, width: 2, height: 1 },
+    Rect { x: 5, y: 6, width: 2, height: 1 },
+    Rect { x: 9, y: 6, width: 2, height: 1 },
+    Rect { x: 2, y: 7, width: 2, height: 1 },
+    Rect { x: 8, y: 7, width: 2, height: 1 },
+];
+
+pub const ALIEN_MYSTERY: &[Rect] = &[
+    Rect { x: 4, y: 0, width: 4, height: 1 },
+    Rect { x: 2, y: 1, width: 8, height: 1 },
+    Rect { x: 1, y: 2, width: 10, height: 1 },
+    Rect { x: 0, y: 3, width: 2, height: 1 },
+    Rect { x: 3, y: 3, width: 2, height: 1 },
+    Rect { x: 7, y: 3, width: 2, height: 1 },
+    Rect { x: 10, y: 3, width: 2, height: 1 },
+    Rect { x: 0, y: 4, width: 12, height: 1 },
+    Rect { x: 2, y: 5, width: 3, height: 1 },
+    Rect { x: 7, y: 5, width: 3, height: 1 },
+    Rect { x: 3, y: 6, width: 1, height: 1 },
+    Rect { x: 8, y: 6, width: 1, height: 1 },
+];
+
+/// An undamaged bunker, see [`Bunker::sprite`] for the actual shape of a bunker
+pub const BUNKER: &[Rect] = &[
+    Rect { x: 3, y: 0, width: 18, height: 1 },
+    Rect { x: 2, y: 1, width: 20, height: 1 },
+    Rect { x: 1, y: 2, width: 22, height: 1 },
+    Rect { x: 0, y: 3, width: 6, height: 15 },
+    Rect { x: 18, y: 3, width: 6, height: 15 },
+    Rect { x: 6, y: 3, width: 12, height: 5 },
+    Rect { x: 6, y: 8, width: 4, height: 1 },
+    Rect { x: 6, y: 9, width: 3, height: 1 },
+    Rect { x: 6, y: 10, width: 2, height: 1 },
+    Rect { x: 6, y: 11, width: 1, height: 1 },
+    Rect { x: 14, y: 8, width: 4, height: 1 },
+    Rect { x: 15, y: 9, width: 3, height: 1 },
+    Rect { x: 16, y: 10, width: 2, height: 1 },
+    Rect { x: 17, y: 11, width: 1, height: 1 },
+];
+
+pub const CANNON: &[Rect] = &[
+    Rect { x: 7, y: 0, width: 1, height: 1 },
+    Rect { x: 6, y: 1, width: 3, height: 2 },
+    Rect { x: 1, y: 3, width: 13, height: 1 },
+    Rect { x: 0, y: 4, width: 15, height: 4 },
+];
+
+pub const BULLET: &[Rect] = &[
+    Rect { x: 0, y: 0, width: Bullet::WIDTH, height: Bullet::HEIGHT },
+];
+
+impl Bunker {
+    /// The intact pixels of the bunker, as one rectangle per horizontal run of pixels
+    pub fn sprite(&self) -> Vec<Rect> {
+        let mut sprite = Vec::new();
+        for (y, &row) in self.mask().iter().enumerate() {
+            let mut x = 0;
+            while x < Bunker::WIDTH {
+                if row & 1 << x == 0 {
+                    x += 1;
+                    continue;
+                }
+
+                let start = x;
+                while x < Bunker::WIDTH && row & 1 << x != 0 {
+                    x += 1;
+                }
+                sprite.push(Rect { x: start, y, width: x - start, height: 1 });
+            }
+        }
+
+        sprite
+    }
+}
+
+impl AlienType {
+    pub fn sprite(&self) -> &'static [Rect] {
+        match self {
+            Self::Mystery => ALIEN_MYSTERY,
+            Self::Hard => ALIEN_HARD,
+            Self::Medium => ALIEN_MEDIUM,
+            Self::Easy => ALIEN_EASY,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Mystery => Color::RED,
+            _ => Color::WHITE,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorMode {
+    /// One byte per pixel
+    Grayscale,
+    /// Three bytes per pixel, in the order red, green, blue
+    Rgb,
+}
+
+impl ColorMode {
+    pub fn channels(&self) -> usize {
+        match self {
+            Self::Grayscale => 1,
+            Self::Rgb => 3,
+        }
+    }
+}
+
+/// A row-major pixel buffer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    color_mode: ColorMode,
+    pixels: Vec<u8>,
+}
+
+impl Frame {
+    /// Creates a black frame
+    pub fn new(width: usize, height: usize, color_mode: ColorMode) -> Self {
+        Self {
+            width,
+            height,
+            color_mode,
+            pixels: vec![0; width * height * color_mode.channels()],
+        }
+    }
+
+    /// Renders `play_field` in its full resolution of [`PlayField::WIDTH`] x [`PlayField::HEIGHT`]
+    pub fn render(play_field: &PlayField, color_mode: ColorMode) -> Self {
+        let mut frame = Self::new(PlayField::WIDTH, PlayField::HEIGHT, color_mode);
+        draw(play_field, &mut frame);
+
+        frame
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    /// Shrinks the frame by `factor` in both dimensions, by averaging blocks of `factor` x
+    /// `factor` pixels
+    pub fn downsample(&self, factor: usize) -> Self {
+        let factor = factor.max(1);
+        let channels = self.color_mode.channels();
+        let mut frame = Self::new(self.width / factor, self.height / factor, self.color_mode);
+
+        for y in 0..frame.height {
+            for x in 0..frame.width {
+                for channel in 0..channels {
+                    let sum = (0..factor)
+                        .flat_map(|dy| (0..factor).map(move |dx| (dx, dy)))
+                        .map(|(dx, dy)| {
+                            self.pixels[self.index(x * factor + dx, y * factor + dy) + channel] as usize
+                        })
+                        .sum::<usize>();
+
+                    let index = frame.index(x, y) + channel;
+                    frame.pixels[index] = (sum / (factor * factor)) as u8;
+                }
+            }
+        }
+
+        frame
+    }
+
+    /// The pixel-wise maximum of two frames of the same size and color mode
+    ///
+    /// Max-pooling the last two frames of a [frame skip](crate::frame_skip) keeps bullets visible,
+    /// that only existed in one of them.
+    pub fn max(&self, other: &Self) -> Self {
+        assert_eq!(
+            (self.width, self.height, self.color_mode),
+            (other.width, other.height, other.color_mode),
+            "only frames of the same shape can be pooled",
+        );
+
+        Self {
+            pixels: self.pixels
+                .iter()
+                .zip(other.pixels.iter())
+                .map(|(a, b)| *a.max(b))
+                .collect(),
+            ..*self
+        }
+    }
+
+    /// Fills a rectangle, clipped to the frame
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Color) {
+        for y in y..(y + height).min(self.height) {
+            for x in x..(x + width).min(self.width) {
+                self.set_pixel(x, y, color);
+            }
+        }
+    }
+
+    pub fn draw_sprite(&mut self, sprite: &[Rect], position: Position, color: Color) {
+        draw_sprite(self, sprite, position, color);
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+        let index = self.index(x, y);
+        match self.color_mode {
+            ColorMode::Grayscale => self.pixels[index] = color.gray(),
+            ColorMode::Rgb => self.pixels[index..index + 3].copy_from_slice(&[color.r, color.g, color.b]),
+        }
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        (y * self.width + x) * self.color_mode.channels()
+    }
+}
+
+impl Canvas for Frame {
+    fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Color) {
+        Frame::fill_rect(self, x, y, width, height, color);
+    }
+}
+
+/// Draws `play_field` with the same sprites and colors on any canvas
+pub fn draw(play_field: &PlayField, canvas: &mut dyn Canvas) {
+    draw_world(&play_field.world, canvas);
+    draw_sprite(canvas, CANNON, play_field.cannon().position(), Color::GREEN);
+}
+
+/// Draws `versus` like [`draw`], with the cannon of [`Player::Two`] in white
+///
+/// Cannons, that are off the field, are not drawn.
+pub fn draw_versus(versus: &Versus, canvas: &mut dyn Canvas) {
+    draw_world(versus.world(), canvas);
+    for (player, color) in Player::BOTH.iter().zip(&[Color::GREEN, Color::WHITE]) {
+        let contender = versus.player(*player);
+        if contender.is_active() {
+            draw_sprite(canvas, CANNON, contender.cannon().position(), *color);
+        }
+    }
+}
+
+fn draw_world(world: &World, canvas: &mut dyn Canvas) {
+    world
+        .bullets
+        .iter()
+        .for_each(|bullet| draw_sprite(canvas, BULLET, bullet.position(), Color::WHITE));
+    world
+        .aliens
+        .iter()
+        .flat_map(|col| col.iter())
+        .flatten()
+        .chain(&world.mystery)
+        .for_each(|alien| draw_alien(canvas, alien));
+    world
+        .bunkers
+        .iter()
+        .flatten()
+        .for_each(|bunker| draw_sprite(canvas, &bunker.sprite(), bunker.position(), Color::GREEN));
+}
+
+pub fn draw_sprite(canvas: &mut dyn Canvas, sprite: &[Rect], position: Position, color: Color) {
+    sprite
+        .iter()
+        .for_each(|rect| {
+            canvas.fill_rect(position.x + rect.x, position.y + rect.y, rect.width, rect.height, color)
+        });
+}
+
+fn draw_alien(canvas: &mut dyn Canvas, alien: &Alien) {
+    let alien_type = alien.alien_type();
+    draw_sprite(canvas, alien_type.sprite(), alien.position(), alien_type.color());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(frame: &Frame, x: usize, y: usize) -> &[u8] {
+        let index = frame.index(x, y);
+        &frame.pixels()[index..index + frame.color_mode().channels()]
+    }
+
+    #[test]
+    fn frames_have_the_size_of_the_play_field() {
+        let play_field = PlayField::with_seed(0);
+
+        for &color_mode in &[ColorMode::Grayscale, ColorMode::Rgb] {
+            let frame = Frame::render(&play_field, color_mode);
+            assert_eq!((frame.width(), frame.height()), (224, 256));
+            assert_eq!(frame.pixels().len(), 224 * 256 * color_mode.channels());
+        }
+    }
+
+    #[test]
+    fn downsampling_averages_blocks() {
+        let mut frame = Frame::new(4, 4, ColorMode::Grayscale);
+        frame.fill_rect(0, 0, 1, 1, Color::WHITE);
+        frame.fill_rect(2, 2, 2, 2, Color::WHITE);
+
+        let small = frame.downsample(2);
+        assert_eq!((small.width(), small.height()), (2, 2));
+        assert_eq!(small.pixels(), [0xFF / 4, 0, 0, 0xFF]);
+    }
+
+    #[test]
+    fn downsampling_averages_every_channel() {
+        let mut frame = Frame::new(4, 2, ColorMode::Rgb);
+        frame.fill_rect(0, 0, 2, 2, Color::RED);
+        frame.fill_rect(2, 0, 1, 2, Color::WHITE);
+
+        let small = frame.downsample(2);
+        assert_eq!(small.pixels(), [0xFF, 0x60, 0x00, 0xFF / 2, 0xFF / 2, 0xFF / 2]);
+    }
+
+    #[test]
+    fn sprites_are_drawn_at_their_position() {
+        let mut frame = Frame::new(32, 32, ColorMode::Rgb);
+        frame.draw_sprite(CANNON, Position { x: 10, y: 20 }, Color::GREEN);
+
+        let green = [Color::GREEN.r, Color::GREEN.g, Color::GREEN.b];
+        assert_eq!(pixel(&frame, 17, 20), green);
+        assert_eq!(pixel(&frame, 16, 20), [0, 0, 0]);
+        assert_eq!(pixel(&frame, 10, 24), green);
+        assert_eq!(pixel(&frame, 24, 27), green);
+        assert_eq!(pixel(&frame, 25, 27), [0, 0, 0]);
+        assert_eq!(pixel(&frame, 10, 28), [0, 0, 0]);
+
+        let lit = frame.pixels().chunks(3).filter(|pixel| *pixel == green).count();
+        assert_eq!(lit, CANNON.iter().map(|rect| rect.width * rect.height).sum::<usize>());
+    }
+
+    #[test]
+    fn the_cannon_is_rendered_where_it_is() {
+        let play_field = PlayField::with_seed(0);
+        let frame = Frame::render(&play_field, ColorMode::Grayscale);
+        let cannon = play_field.cannon().position();
+
+        assert_eq!(pixel(&frame, cannon.x + 7, cannon.y), [Color::GREEN.gray()]);
+        assert_eq!(pixel(&frame, cannon.x, cannon.y), [0]);
+    }
+}
@@ -0,0 +1,590 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+use core::hash::Hasher;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{GameStatus, Input, Instruction, PlayField, Score};
+use crate::config::{GameConfig, ShootProbability};
+use crate::event::Event;
+
+/// The version of the engine, that recorded a replay
+///
+/// Replays can only be played back by the exact same engine version, since every change to the
+/// game logic changes the outcome of a replay. That's why the crate version is bumped with every
+/// change to the rules, even before the first release.
+pub const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The number of steps between two checksums of a replay
+///
+/// The checksums are used to find the step, in which a playback diverged from the recorded game.
+pub const CHECKPOINT_INTERVAL: usize = 60;
+/// The maximum number of steps of a replay, about three days of playing time at 60 steps per
+/// second
+pub const MAX_INPUTS: usize = 1 << 24;
+
+const MAGIC: &[u8; 4] = b"SIRP";
+// stays at 1 until the format is first released
+const FORMAT_VERSION: u8 = 1;
+
+impl Input {
+    fn to_byte(self) -> u8 {
+        let instruction = match self.instruction {
+            Instruction::None => 0,
+            Instruction::MoveLeft => 1,
+            Instruction::MoveRight => 2,
+        };
+
+        instruction | (self.shoot as u8) << 2
+    }
+
+    fn from_byte(byte: u8) -> Result<Self, ReplayError> {
+        let instruction = match byte & 0b11 {
+            0 => Instruction::None,
+            1 => Instruction::MoveLeft,
+            2 => Instruction::MoveRight,
+            _ => return Err(ReplayError::InvalidFormat),
+        };
+        if byte >> 3 != 0 {
+            return Err(ReplayError::InvalidFormat);
+        }
+
+        Ok(Self {
+            instruction,
+            shoot: byte & 0b100 != 0,
+        })
+    }
+}
+
+/// A recorded game, that can be re-simulated step by step
+///
+/// Since the engine is deterministic, a replay only consists of the seed, the [`GameConfig`] and every
+/// [`Input`] the [`PlayField`] received. Checksums of the state every [`CHECKPOINT_INTERVAL`] steps
+/// and of the final state are used to verify, that the playback is identical to the recorded game.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Replay {
+    engine_version: String,
+    seed: u64,
+    config: GameConfig,
+    inputs: Vec<Input>,
+    // the checksum after every `CHECKPOINT_INTERVAL` steps
+    checkpoints: Vec<u64>,
+    checksum: u64,
+}
+
+impl Replay {
+    /// Starts recording the game of `play_field`
+    ///
+    /// The recording has to start before the first step of the game.
+    pub fn new(play_field: &PlayField) -> Self {
+        Self {
+            engine_version: ENGINE_VERSION.to_string(),
+            seed: play_field.seed(),
+            config: *play_field.config(),
+            inputs: Vec::new(),
+            checkpoints: Vec::new(),
+            checksum: play_field.checksum(),
+        }
+    }
+
+    /// Records one step, after it was applied to `play_field`
+    pub fn record(&mut self, play_field: &PlayField, instruction: Instruction, shoot: bool) {
+        self.inputs.push(Input { instruction, shoot });
+        self.checksum = play_field.checksum();
+        if self.inputs.len() % CHECKPOINT_INTERVAL == 0 {
+            self.checkpoints.push(self.checksum);
+        }
+    }
+
+    pub fn engine_version(&self) -> &str {
+        &self.engine_version
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The rules the game was played with
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn inputs(&self) -> &[Input] {
+        &self.inputs
+    }
+
+    /// Re-simulates the recorded game
+    pub fn play(&self) -> Result<Playback<'_>, ReplayError> {
+        if self.engine_version != ENGINE_VERSION {
+            return Err(ReplayError::EngineVersionMismatch { recorded: self.engine_version.clone() });
+        }
+        // only `from_bytes` checks this, so deserialized replays could miss checkpoints
+        if self.checkpoints.len() != self.inputs.len() / CHECKPOINT_INTERVAL {
+            return Err(ReplayError::InvalidFormat);
+        }
+
+        let play_field = PlayField::with_seed_and_config(self.seed, self.config);
+
+        Ok(Playback {
+            replay: self,
+            play_field,
+            tick: 0,
+            mismatch: None,
+        })
+    }
+
+    /// Re-simulates the whole game and checks, that it passes every checkpoint and ends in exactly
+    /// the recorded state
+    pub fn verify(&self) -> Result<GameStatus, ReplayError> {
+        let mut playback = self.play()?;
+        playback.by_ref().for_each(drop);
+        playback.verify()?;
+
+        Ok(playback.play_field().status())
+    }
+
+    /// Encodes the replay in a compact binary format
+    ///
+    /// Consecutive equal inputs are run-length encoded, so idle stretches of a game cost only
+    /// a few bytes.
+    ///
+    /// Fails, if the engine version is longer than 255 bytes, which is only possible for replays,
+    /// that were deserialized from another format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ReplayError> {
+        let version_len = u8::try_from(self.engine_version.len()).map_err(|_| ReplayError::InvalidFormat)?;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(FORMAT_VERSION);
+        bytes.push(version_len);
+        bytes.extend_from_slice(self.engine_version.as_bytes());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        write_config(&mut bytes, &self.config);
+        bytes.extend_from_slice(&self.checksum.to_le_bytes());
+        write_var_int(&mut bytes, self.checkpoints.len() as u64);
+        for checkpoint in &self.checkpoints {
+            bytes.extend_from_slice(&checkpoint.to_le_bytes());
+        }
+
+        let mut inputs = self.inputs.iter().peekable();
+        while let Some(input) = inputs.next() {
+            let mut run = 1;
+            while inputs.next_if_eq(&input).is_some() {
+                run += 1;
+            }
+
+            write_var_int(&mut bytes, run);
+            bytes.push(input.to_byte());
+        }
+
+        Ok(bytes)
+    }
+
+    /// Decodes a replay, that was encoded by [`Replay::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut reader = Reader { bytes };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(ReplayError::InvalidFormat);
+        }
+        let format_version = reader.byte()?;
+        if format_version != FORMAT_VERSION {
+            return Err(ReplayError::UnsupportedFormatVersion(format_version));
+        }
+
+        let version_len = reader.byte()? as usize;
+        let engine_version = core::str::from_utf8(reader.take(version_len)?)
+            .map_err(|_| ReplayError::InvalidFormat)?
+            .to_string();
+        let seed = reader.u64()?;
+        let config = reader.config()?;
+        config.validate().map_err(|_| ReplayError::InvalidFormat)?;
+        let checksum = reader.u64()?;
+
+        // every checkpoint takes 8 bytes, so the count can't be larger than the rest of the replay
+        let checkpoint_count = reader.var_int()?;
+        if checkpoint_count > (reader.bytes.len() / 8) as u64 {
+            return Err(ReplayError::InvalidFormat);
+        }
+        let checkpoints = (0..checkpoint_count)
+            .map(|_| reader.u64())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut inputs = Vec::new();
+        while !reader.bytes.is_empty() {
+            let run = usize::try_from(reader.var_int()?).map_err(|_| ReplayError::TooLong)?;
+            let input = Input::from_byte(reader.byte()?)?;
+            let len = inputs
+                .len()
+                .checked_add(run)
+                .filter(|&len| len <= MAX_INPUTS)
+                .ok_or(ReplayError::TooLong)?;
+            inputs.resize(len, input);
+        }
+
+        if checkpoints.len() != inputs.len() / CHECKPOINT_INTERVAL {
+            return Err(ReplayError::InvalidFormat);
+        }
+
+        Ok(Self {
+            engine_version,
+            seed,
+            config,
+            inputs,
+            checkpoints,
+            checksum,
+        })
+    }
+}
+
+/// One step of a [`Playback`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub tick: usize,
+    pub input: Input,
+    pub status: GameStatus,
+    /// Everything, that happened during the step
+    pub events: Vec<Event>,
+}
+
+/// Re-simulates a [`Replay`] one step at a time
+///
+/// The current state of the game is available through [`Playback::play_field`] between two
+/// frames.
+pub struct Playback<'r> {
+    replay: &'r Replay,
+    play_field: PlayField,
+    tick: usize,
+    // the first checkpoint, that the playback missed
+    mismatch: Option<ReplayError>,
+}
+
+impl Playback<'_> {
+    pub fn play_field(&self) -> &PlayField {
+        &self.play_field
+    }
+
+    /// Checks, that the re-simulated game passed every checkpoint so far, and, after the last
+    /// frame, that it ended in exactly the recorded state
+    ///
+    /// The error contains the first tick, at which the playback was found to diverge.
+    pub fn verify(&self) -> Result<(), ReplayError> {
+        if let Some(mismatch) = &self.mismatch {
+            return Err(mismatch.clone());
+        }
+        if self.tick < self.replay.inputs.len() {
+            return Ok(());
+        }
+
+        Self::compare(self.tick, self.replay.checksum, self.play_field.checksum())
+    }
+
+    fn compare(tick: usize, expected: u64, actual: u64) -> Result<(), ReplayError> {
+        match actual == expected {
+            true => Ok(()),
+            false => Err(ReplayError::ChecksumMismatch { tick, expected, actual }),
+        }
+    }
+}
+
+impl Iterator for Playback<'_> {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let input = *self.replay.inputs.get(self.tick)?;
+        let status = self.play_field.step(input.instruction, input.shoot);
+        self.tick += 1;
+
+        if self.mismatch.is_none() && self.tick % CHECKPOINT_INTERVAL == 0 {
+            let expected = self.replay.checkpoints[self.tick / CHECKPOINT_INTERVAL - 1];
+            self.mismatch = Self::compare(self.tick, expected, self.play_field.checksum()).err();
+        }
+
+        Some(Frame {
+            tick: self.tick,
+            input,
+            status,
+            events: self.play_field.drain_events().collect(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplayError {
+    InvalidFormat,
+    UnsupportedFormatVersion(u8),
+    EngineVersionMismatch { recorded: String },
+    /// The replay has more than [`MAX_INPUTS`] steps
+    TooLong,
+    /// The playback was not in the recorded state at `tick`
+    ChecksumMismatch { tick: usize, expected: u64, actual: u64 },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFormat => write!(f, "invalid replay format"),
+            Self::UnsupportedFormatVersion(version) => write!(f, "unsupported replay format version {}", version),
+            Self::EngineVersionMismatch { recorded } => write!(
+                f,
+                "replay was recorded with engine version {}, but this is version {}",
+                recorded,
+                ENGINE_VERSION
+            ),
+            Self::TooLong => write!(f, "replay is longer than {} steps", MAX_INPUTS),
+            Self::ChecksumMismatch { tick, expected, actual } => write!(
+                f,
+                "playback diverged at tick {}: state {:016x}, but the recorded game was in state {:016x}",
+                tick,
+                actual,
+                expected
+            ),
+        }
+    }
+}
+
+/// A 64 bit FNV-1a hasher, that produces the same hashes on every platform
+///
+/// `usize`s and `isize`s are always hashed as 64 bit integers and all integers are hashed in
+/// little endian byte order, so a wasm32 build calculates the same checksums as a native one.
+pub(crate) struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_i64(i as i64);
+    }
+}
+
+fn write_var_int(bytes: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn write_config(bytes: &mut Vec<u8>, config: &GameConfig) {
+    write_var_int(bytes, config.alien_rows as u64);
+    write_var_int(bytes, config.alien_columns as u64);
+    write_var_int(bytes, config.lives as u64);
+    write_var_int(bytes, config.bunkers as u64);
+    write_var_int(bytes, config.bunker_erosion as u64);
+    let probabilities = &config.shoot_probability;
+    for probability in &[probabilities.hard, probabilities.medium, probabilities.easy] {
+        bytes.extend_from_slice(&probability.to_bits().to_le_bytes());
+    }
+    write_var_int(bytes, config.cannon_speed as u64);
+    write_var_int(bytes, config.max_player_bullets.map_or(0, |max_bullets| max_bullets as u64 + 1));
+    write_var_int(bytes, config.fire_cooldown as u64);
+    write_var_int(bytes, config.respawn_ticks as u64);
+    bytes.push(config.bullet_collisions as u8);
+    bytes.extend_from_slice(&config.bullet_collision_score.to_le_bytes());
+    write_var_int(bytes, config.mystery_interval as u64);
+    write_var_int(bytes, config.max_waves.map_or(0, |max_waves| max_waves as u64 + 1));
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+}
+
+impl<'b> Reader<'b> {
+    fn take(&mut self, len: usize) -> Result<&'b [u8], ReplayError> {
+        if self.bytes.len() < len {
+            return Err(ReplayError::InvalidFormat);
+        }
+
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, ReplayError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64, ReplayError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn f64(&mut self) -> Result<f64, ReplayError> {
+        Ok(f64::from_bits(self.u64()?))
+    }
+
+    fn config(&mut self) -> Result<GameConfig, ReplayError> {
+        let alien_rows = self.var_int()? as usize;
+        let alien_columns = self.var_int()? as usize;
+        let lives = self.var_int()? as usize;
+        let bunkers = self.var_int()? as usize;
+        let bunker_erosion = self.var_int()? as usize;
+        let shoot_probability = ShootProbability {
+            hard: self.f64()?,
+            medium: self.f64()?,
+            easy: self.f64()?,
+        };
+        let cannon_speed = self.var_int()? as usize;
+        let max_player_bullets = self.optional_var_int()?;
+        let fire_cooldown = self.var_int()? as usize;
+        let respawn_ticks = self.var_int()? as usize;
+        let bullet_collisions = match self.byte()? {
+            0 => false,
+            1 => true,
+            _ => return Err(ReplayError::InvalidFormat),
+        };
+        let bullet_collision_score = self.u64()? as Score;
+        let mystery_interval = self.var_int()? as usize;
+        let max_waves = self.optional_var_int()?;
+
+        Ok(GameConfig {
+            alien_rows,
+            alien_columns,
+            lives,
+            bunkers,
+            bunker_erosion,
+            shoot_probability,
+            cannon_speed,
+            max_player_bullets,
+            fire_cooldown,
+            respawn_ticks,
+            bullet_collisions,
+            bullet_collision_score,
+            mystery_interval,
+            max_waves,
+        })
+    }
+
+    /// Reads a var int, that was written as `0` for `None` and `n + 1` for `Some(n)`
+    fn optional_var_int(&mut self) -> Result<Option<usize>, ReplayError> {
+        Ok(match self.var_int()? {
+            0 => None,
+            n => Some((n - 1) as usize),
+        })
+    }
+
+    fn var_int(&mut self) -> Result<u64, ReplayError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(ReplayError::InvalidFormat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(steps: usize) -> Replay {
+        let mut play_field = PlayField::with_seed(7);
+        let mut replay = Replay::new(&play_field);
+        for tick in 0..steps {
+            let instruction = match tick / 20 % 2 {
+                0 => Instruction::MoveLeft,
+                _ => Instruction::MoveRight,
+            };
+            play_field.step(instruction, false);
+            replay.record(&play_field, instruction, false);
+        }
+
+        replay
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let replay = record(200);
+        let decoded = Replay::from_bytes(&replay.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(decoded, replay);
+        assert!(decoded.verify().is_ok());
+    }
+
+    #[test]
+    fn oversized_run_is_rejected() {
+        let mut bytes = record(0).to_bytes().unwrap();
+        write_var_int(&mut bytes, u64::MAX);
+        bytes.push(Input { instruction: Instruction::None, shoot: false }.to_byte());
+
+        assert_eq!(Replay::from_bytes(&bytes), Err(ReplayError::TooLong));
+    }
+
+    #[test]
+    fn missing_checkpoints_are_rejected() {
+        let mut replay = record(200);
+        replay.checkpoints.pop();
+
+        assert!(matches!(replay.play(), Err(ReplayError::InvalidFormat)));
+        assert!(matches!(replay.verify(), Err(ReplayError::InvalidFormat)));
+    }
+
+    #[test]
+    fn verify_reports_the_first_diverging_checkpoint() {
+        let mut replay = record(200);
+        replay.inputs[70].instruction = Instruction::None;
+
+        assert!(matches!(
+            replay.verify(),
+            Err(ReplayError::ChecksumMismatch { tick, .. }) if tick == 2 * CHECKPOINT_INTERVAL
+        ));
+    }
+}
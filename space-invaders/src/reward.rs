@@ -0,0 +1,265 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use core::ops::AddAssign;
+
+use crate::Score;
+use crate::alien::AlienType;
+use crate::env::Reward;
+
+/// One value for each [`AlienType`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PerAlienType<T> {
+    pub mystery: T,
+    pub hard: T,
+    pub medium: T,
+    pub easy: T,
+}
+
+impl<T> PerAlienType<T> {
+    pub fn get(&self, alien_type: AlienType) -> &T {
+        match alien_type {
+            AlienType::Mystery => &self.mystery,
+            AlienType::Hard => &self.hard,
+            AlienType::Medium => &self.medium,
+            AlienType::Easy => &self.easy,
+        }
+    }
+
+    pub fn get_mut(&mut self, alien_type: AlienType) -> &mut T {
+        match alien_type {
+            AlienType::Mystery => &mut self.mystery,
+            AlienType::Hard => &mut self.hard,
+            AlienType::Medium => &mut self.medium,
+            AlienType::Easy => &mut self.easy,
+        }
+    }
+}
+
+/// Sums the values of each alien type
+impl<T: AddAssign> AddAssign for PerAlienType<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.mystery += rhs.mystery;
+        self.hard += rhs.hard;
+        self.medium += rhs.medium;
+        self.easy += rhs.easy;
+    }
+}
+
+/// Everything, that happened during one step of a [`PlayField`](crate::PlayField)
+///
+/// The tallies of consecutive steps can be summed with `+=`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StepTally {
+    /// The change of the displayed score
+    pub score: Score,
+    pub kills: PerAlienType<usize>,
+    pub shots_fired: usize,
+    /// The number of player bullets, that left the field without hitting anything
+    pub misses: usize,
+    pub lives_lost: usize,
+    /// The number of bunker pixels, that were destroyed by bullets or aliens
+    pub bunker_damage: usize,
+    /// The number of alien bullets, that were destroyed by player bullets
+    pub bullets_shot_down: usize,
+    pub waves_cleared: usize,
+    /// `1` for every step, that did not end the game
+    pub steps_survived: usize,
+}
+
+impl AddAssign for StepTally {
+    fn add_assign(&mut self, rhs: Self) {
+        self.score += rhs.score;
+        self.kills += rhs.kills;
+        self.shots_fired += rhs.shots_fired;
+        self.misses += rhs.misses;
+        self.lives_lost += rhs.lives_lost;
+        self.bunker_damage += rhs.bunker_damage;
+        self.bullets_shot_down += rhs.bullets_shot_down;
+        self.waves_cleared += rhs.waves_cleared;
+        self.steps_survived += rhs.steps_survived;
+    }
+}
+
+/// Weights for shaping the reward of the [`Environment`](crate::env::Environment)
+///
+/// The reward of a step is the weighted sum of its [`StepTally`]. The displayed score is not
+/// affected by the reward config.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RewardConfig {
+    /// The reward per point of the displayed score
+    pub score: Reward,
+    pub kill: PerAlienType<Reward>,
+    pub shot_fired: Reward,
+    pub miss: Reward,
+    pub life_lost: Reward,
+    pub bunker_damage: Reward,
+    pub bullet_shot_down: Reward,
+    pub wave_cleared: Reward,
+    pub step_survived: Reward,
+}
+
+impl RewardConfig {
+    /// A config, where every weight is `0`
+    pub const ZERO: Self = Self {
+        score: 0.,
+        kill: PerAlienType { mystery: 0., hard: 0., medium: 0., easy: 0. },
+        shot_fired: 0.,
+        miss: 0.,
+        life_lost: 0.,
+        bunker_damage: 0.,
+        bullet_shot_down: 0.,
+        wave_cleared: 0.,
+        step_survived: 0.,
+    };
+
+    pub fn reward(&self, tally: &StepTally) -> Reward {
+        let kills = self.kill.mystery * tally.kills.mystery as Reward
+            + self.kill.hard * tally.kills.hard as Reward
+            + self.kill.medium * tally.kills.medium as Reward
+            + self.kill.easy * tally.kills.easy as Reward;
+
+        self.score * tally.score as Reward
+            + kills
+            + self.shot_fired * tally.shots_fired as Reward
+            + self.miss * tally.misses as Reward
+            + self.life_lost * tally.lives_lost as Reward
+            + self.bunker_damage * tally.bunker_damage as Reward
+            + self.bullet_shot_down * tally.bullets_shot_down as Reward
+            + self.wave_cleared * tally.waves_cleared as Reward
+            + self.step_survived * tally.steps_survived as Reward
+    }
+}
+
+/// The reward is the change of the displayed score
+impl Default for RewardConfig {
+    fn default() -> Self {
+        Self {
+            score: 1.,
+            ..Self::ZERO
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Input, PlayField, Player, Position};
+    use crate::event::Event;
+
+    use super::*;
+
+    const POSITION: Position = Position { x: 0, y: 0 };
+
+    fn tally() -> StepTally {
+        StepTally {
+            score: 2,
+            kills: PerAlienType { mystery: 3, hard: 5, medium: 7, easy: 11 },
+            shots_fired: 13,
+            misses: 17,
+            lives_lost: 19,
+            bunker_damage: 23,
+            bullets_shot_down: 29,
+            waves_cleared: 31,
+            steps_survived: 37,
+        }
+    }
+
+    #[test]
+    fn reward_is_the_weighted_sum_of_the_tally() {
+        let tally = tally();
+        let weighted: [(fn(&mut RewardConfig), Reward); 12] = [
+            (|config| config.score = 2., 2. * 2.),
+            (|config| config.kill.mystery = 2., 2. * 3.),
+            (|config| config.kill.hard = 2., 2. * 5.),
+            (|config| config.kill.medium = 2., 2. * 7.),
+            (|config| config.kill.easy = 2., 2. * 11.),
+            (|config| config.shot_fired = 2., 2. * 13.),
+            (|config| config.miss = 2., 2. * 17.),
+            (|config| config.life_lost = 2., 2. * 19.),
+            (|config| config.bunker_damage = 2., 2. * 23.),
+            (|config| config.bullet_shot_down = 2., 2. * 29.),
+            (|config| config.wave_cleared = 2., 2. * 31.),
+            (|config| config.step_survived = 2., 2. * 37.),
+        ];
+
+        let mut all = RewardConfig::ZERO;
+        for (set_weight, reward) in weighted.iter() {
+            let mut config = RewardConfig::ZERO;
+            set_weight(&mut config);
+            assert_eq!(config.reward(&tally), *reward);
+            set_weight(&mut all);
+        }
+        assert_eq!(all.reward(&tally), weighted.iter().map(|(_, reward)| reward).sum::<Reward>());
+        assert_eq!(RewardConfig::ZERO.reward(&tally), 0.);
+        assert_eq!(RewardConfig::default().reward(&tally), 2.);
+    }
+
+    #[test]
+    fn tallies_are_summed() {
+        let mut sum = tally();
+        sum += tally();
+        assert_eq!(RewardConfig { score: 1., ..RewardConfig::ZERO }.reward(&sum), 4.);
+        assert_eq!(sum.kills, PerAlienType { mystery: 6, hard: 10, medium: 14, easy: 22 });
+        assert_eq!(sum.steps_survived, 74);
+    }
+
+    #[test]
+    fn events_are_recorded() {
+        let events = [
+            Event::ShotFired { player: Player::One, position: POSITION },
+            Event::ShotFired { player: Player::One, position: POSITION },
+            Event::BulletExpired { player: Some(Player::One), position: POSITION },
+            Event::BulletExpired { player: None, position: POSITION },
+            Event::BulletShotDown { player: Player::One, position: POSITION },
+            Event::AlienKilled { player: Player::One, alien_type: AlienType::Medium, position: POSITION, points: 20 },
+            Event::BunkerHit { player: None, position: POSITION },
+            Event::CannonHit { player: Player::One, position: POSITION, lives_left: 2 },
+            Event::WaveCleared { wave: 1 },
+            Event::MysterySpawned { position: POSITION },
+        ];
+
+        let mut tally = StepTally::default();
+        events.iter().for_each(|event| tally.record(event));
+
+        assert_eq!(tally, StepTally {
+            kills: PerAlienType { medium: 1, ..PerAlienType::default() },
+            shots_fired: 2,
+            misses: 1,
+            lives_lost: 1,
+            bullets_shot_down: 1,
+            waves_cleared: 1,
+            ..StepTally::default()
+        });
+    }
+
+    #[test]
+    fn reward_config_does_not_change_the_score() {
+        let mut default = PlayField::with_seed(7);
+        let mut shaped = PlayField::with_seed(7);
+        shaped.set_reward_config(RewardConfig {
+            score: 0.5,
+            shot_fired: -1.,
+            step_survived: 0.25,
+            ..RewardConfig::ZERO
+        });
+
+        let mut rewards = (0., 0.);
+        for tick in 0..1000 {
+            let input = Input::from_index(tick / 5 % Input::COUNT).unwrap();
+            let (default_reward, default_info) = default.step_unobserved(input);
+            let (shaped_reward, shaped_info) = shaped.step_unobserved(input);
+
+            assert_eq!(default_info.score, shaped_info.score);
+            assert_eq!(default_reward, default_info.tally.score as Reward);
+            assert_eq!(shaped_reward, shaped.reward_config().reward(&shaped_info.tally));
+            rewards.0 += default_reward;
+            rewards.1 += shaped_reward;
+        }
+
+        assert_eq!(default.score(), shaped.score());
+        assert_ne!(rewards.0, rewards.1);
+    }
+}
@@ -0,0 +1,247 @@
+//! Two players on two identical fields
+//!
+//! Unlike in a [`Versus`](crate::versus::Versus) game, the players don't share a field. Both
+//! fields start from the same seed and follow the same rules, so the players face exactly the
+//! same invasion, as long as they play the same way.
+
+use alloc::vec::Drain;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{GameStatus, Input, PlayField, Player, Score};
+use crate::config::GameConfig;
+use crate::event::Event;
+use crate::versus::Outcome;
+
+/// Two [`PlayField`]s with the same seed and rules, that are stepped in lock-step
+///
+/// The match ends, when both games are done, or when the tick limit is reached.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Match {
+    play_fields: [PlayField; 2],
+    // the tick, in which the game of each player ended
+    finished_at: [Option<usize>; 2],
+    tick: usize,
+    tick_limit: Option<usize>,
+}
+
+/// The final standings of a [`Match`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MatchResult {
+    /// The score of each player, indexed by [`Player::index`]
+    pub scores: [Score; 2],
+    /// The number of ticks each player survived, indexed by [`Player::index`]
+    pub steps_survived: [usize; 2],
+    pub outcome: Outcome,
+}
+
+impl Match {
+    /// Creates a match with a random seed and without a tick limit
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the config is [invalid](GameConfig::validate).
+    pub fn new(config: GameConfig) -> Self {
+        Self::with_seed(rand::random(), config)
+    }
+
+    /// Creates a match, whose randomness is fully determined by `seed`, without a tick limit
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the config is [invalid](GameConfig::validate).
+    pub fn with_seed(seed: u64, config: GameConfig) -> Self {
+        let play_field = PlayField::with_seed_and_config(seed, config);
+
+        Self {
+            play_fields: [play_field.clone(), play_field],
+            finished_at: [None; 2],
+            tick: 0,
+            tick_limit: None,
+        }
+    }
+
+    /// Ends the match after `tick_limit` ticks, even if the games are still running
+    pub fn tick_limit(mut self, tick_limit: usize) -> Self {
+        self.tick_limit = Some(tick_limit);
+        self
+    }
+
+    pub fn play_field(&self, player: Player) -> &PlayField {
+        &self.play_fields[player.index()]
+    }
+
+    /// Removes and returns the collected events of the game of `player`
+    pub fn drain_events(&mut self, player: Player) -> Drain<'_, Event> {
+        self.play_fields[player.index()].drain_events()
+    }
+
+    /// The number of ticks played so far
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.finished_at.iter().all(Option::is_some)
+            || matches!(self.tick_limit, Some(tick_limit) if self.tick >= tick_limit)
+    }
+
+    /// Advances both games by one tick, player one with `inputs[0]` and player two with
+    /// `inputs[1]`
+    ///
+    /// Once the match [is over](Match::is_over), the games don't change anymore.
+    pub fn step(&mut self, inputs: [Input; 2]) -> [GameStatus; 2] {
+        if !self.is_over() {
+            self.tick += 1;
+            for player in Player::BOTH {
+                let play_field = &mut self.play_fields[player.index()];
+                let input = inputs[player.index()];
+                if play_field.step(input.instruction, input.shoot).is_done() && self.finished_at[player.index()].is_none() {
+                    self.finished_at[player.index()] = Some(self.tick);
+                }
+            }
+        }
+
+        [self.play_fields[0].status(), self.play_fields[1].status()]
+    }
+
+    /// The final standings, or `None` while the match is still running
+    ///
+    /// The player with the higher score wins. Ties are broken by the number of survived ticks.
+    pub fn result(&self) -> Option<MatchResult> {
+        if !self.is_over() {
+            return None;
+        }
+
+        let scores = [self.play_fields[0].score(), self.play_fields[1].score()];
+        let steps_survived = [
+            self.finished_at[0].unwrap_or(self.tick),
+            self.finished_at[1].unwrap_or(self.tick),
+        ];
+        let outcome = match (scores[0], steps_survived[0]).cmp(&(scores[1], steps_survived[1])) {
+            core::cmp::Ordering::Greater => Outcome::Winner(Player::One),
+            core::cmp::Ordering::Less => Outcome::Winner(Player::Two),
+            core::cmp::Ordering::Equal => Outcome::Draw,
+        };
+
+        Some(MatchResult {
+            scores,
+            steps_survived,
+            outcome,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{Bullet, GameObj, Instruction, Position};
+    use crate::alien::{Alien, AlienType};
+    use crate::cannon::Cannon;
+
+    use super::*;
+
+    const IDLE: [Input; 2] = [Input { instruction: Instruction::None, shoot: false }; 2];
+
+    fn one_alien() -> GameConfig {
+        GameConfig {
+            alien_rows: 1,
+            alien_columns: 1,
+            lives: 1,
+            max_waves: Some(1),
+            ..GameConfig::default()
+        }
+    }
+
+    // lets the game of `player` be won in the next tick
+    fn shoot_down_the_alien(game: &mut Match, player: Player) {
+        let play_field = &mut game.play_fields[player.index()];
+        let alien = play_field.aliens().position();
+        play_field.world.bullets = vec![Bullet::player_at_position(
+            Position { x: alien.x + Alien::WIDTH / 2, y: alien.y + Alien::HEIGHT },
+            Player::One,
+        )];
+    }
+
+    // lets the game of `player` be lost in the next tick
+    fn hit_the_cannon(game: &mut Match, player: Player) {
+        let cannon = Cannon::new().position();
+        game.play_fields[player.index()].world.bullets = vec![Bullet::alien_at_position(
+            Position { x: cannon.x + Cannon::WIDTH / 2, y: cannon.y },
+            AlienType::Easy,
+        )];
+    }
+
+    #[test]
+    fn match_ends_at_the_tick_limit() {
+        let mut game = Match::with_seed(0, GameConfig::default()).tick_limit(10);
+
+        for _ in 0..10 {
+            assert!(!game.is_over());
+            assert_eq!(game.result(), None);
+            game.step(IDLE);
+        }
+        assert!(game.is_over());
+
+        let checksum = game.play_field(Player::One).checksum();
+        game.step(IDLE);
+        assert_eq!(game.tick(), 10);
+        assert_eq!(game.play_field(Player::One).checksum(), checksum);
+
+        let result = game.result().unwrap();
+        assert_eq!(result.steps_survived, [10, 10]);
+        assert_eq!(result.outcome, Outcome::Draw);
+    }
+
+    #[test]
+    fn finished_games_keep_their_last_tick() {
+        let mut game = Match::with_seed(0, one_alien()).tick_limit(6);
+        shoot_down_the_alien(&mut game, Player::One);
+
+        assert!(game.step(IDLE)[0].is_done());
+        assert_eq!(game.finished_at, [Some(1), None]);
+
+        for _ in 1..5 {
+            game.step(IDLE);
+            assert_eq!(game.finished_at, [Some(1), None]);
+            assert_eq!(game.result(), None);
+        }
+        game.step(IDLE);
+
+        let result = game.result().unwrap();
+        assert_eq!(result.steps_survived, [1, 6]);
+        assert!(result.scores[0] > result.scores[1]);
+        // the higher score wins, no matter how long the other player survived
+        assert_eq!(result.outcome, Outcome::Winner(Player::One));
+    }
+
+    #[test]
+    fn match_ends_when_both_games_are_done() {
+        let mut game = Match::with_seed(0, one_alien());
+        hit_the_cannon(&mut game, Player::One);
+        game.step(IDLE);
+        hit_the_cannon(&mut game, Player::Two);
+        game.step(IDLE);
+
+        assert!(game.is_over());
+        assert_eq!(game.finished_at, [Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn ties_are_broken_by_the_survived_ticks() {
+        let mut game = Match::with_seed(0, one_alien()).tick_limit(5);
+        hit_the_cannon(&mut game, Player::One);
+        for _ in 0..5 {
+            game.step(IDLE);
+        }
+
+        let result = game.result().unwrap();
+        assert_eq!(result.scores[0], result.scores[1]);
+        assert_eq!(result.steps_survived, [1, 5]);
+        assert_eq!(result.outcome, Outcome::Winner(Player::Two));
+    }
+}
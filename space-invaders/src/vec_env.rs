@@ -0,0 +1,214 @@
+//! Many games stepped as one batch
+//!
+//! With the `parallel` feature, the games are stepped on all cores with rayon. Without it, they
+//! are stepped one after the other, which keeps the crate usable on targets without threads,
+//! like wasm32.
+
+use alloc::vec::Vec;
+
+use rand::{RngCore, SeedableRng};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{GameRng, Input, PlayField};
+use crate::config::GameConfig;
+use crate::env::{ActionSpace, Environment, Info, Observation, Reward};
+use crate::reward::RewardConfig;
+
+/// A batch of [`PlayField`]s with independent seeds
+///
+/// Finished episodes are reset automatically, so every step returns one observation per game.
+/// The seeds of all episodes are drawn from the seed of the batch, so a whole training run is
+/// reproducible.
+#[derive(Clone, Debug)]
+pub struct VecEnv {
+    play_fields: Vec<PlayField>,
+    seeds: GameRng,
+}
+
+/// The outcome of one [`VecEnv::step`], one element per game
+#[derive(Clone, Debug)]
+pub struct VecStep {
+    /// After the end of an episode, the observation is the first one of the next episode
+    pub observations: Vec<Observation>,
+    pub rewards: Vec<Reward>,
+    pub dones: Vec<bool>,
+    /// After the end of an episode, the info still describes the last step of the episode
+    pub infos: Vec<Info>,
+}
+
+impl VecEnv {
+    /// Creates `n` games with the default rules
+    pub fn new(n: usize, seed: u64) -> Self {
+        Self::with_config(n, seed, GameConfig::default())
+    }
+
+    /// Creates `n` games with the given rules
+    ///
+    /// Panics, if the config is invalid.
+    pub fn with_config(n: usize, seed: u64, config: GameConfig) -> Self {
+        let mut seeds = GameRng::seed_from_u64(seed);
+        let play_fields = (0..n)
+            .map(|_| PlayField::with_seed_and_config(seeds.next_u64(), config))
+            .collect();
+
+        Self {
+            play_fields,
+            seeds,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.play_fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.play_fields.is_empty()
+    }
+
+    pub fn play_fields(&self) -> &[PlayField] {
+        &self.play_fields
+    }
+
+    pub fn action_space(&self) -> ActionSpace {
+        ActionSpace::Discrete(Input::COUNT)
+    }
+
+    /// Sets the reward config of every game
+    pub fn set_reward_config(&mut self, reward_config: RewardConfig) {
+        self.play_fields
+            .iter_mut()
+            .for_each(|play_field| play_field.set_reward_config(reward_config));
+    }
+
+    /// Restarts every game, with seeds drawn from `seed`
+    pub fn reset(&mut self, seed: u64) -> Vec<Observation> {
+        self.seeds = GameRng::seed_from_u64(seed);
+        let seeds = &mut self.seeds;
+
+        self.play_fields
+            .iter_mut()
+            .map(|play_field| play_field.reset(seeds.next_u64()))
+            .collect()
+    }
+
+    /// Advances every game by one step, the game at index `i` with `actions[i]`
+    ///
+    /// Panics, if there is not exactly one action per game.
+    pub fn step(&mut self, actions: &[Input]) -> VecStep {
+        assert_eq!(actions.len(), self.play_fields.len(), "there has to be one action per game");
+
+        let results = Self::step_all(&mut self.play_fields, actions);
+
+        let mut step = VecStep {
+            observations: Vec::with_capacity(results.len()),
+            rewards: Vec::with_capacity(results.len()),
+            dones: Vec::with_capacity(results.len()),
+            infos: Vec::with_capacity(results.len()),
+        };
+        // the games are reset in order, so the seeds don't depend on the scheduling of threads
+        for (play_field, (observation, reward, done, info)) in self.play_fields.iter_mut().zip(results) {
+            let observation = match done {
+                true => play_field.reset(self.seeds.next_u64()),
+                false => observation,
+            };
+
+            step.observations.push(observation);
+            step.rewards.push(reward);
+            step.dones.push(done);
+            step.infos.push(info);
+        }
+
+        step
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    fn step_all(play_fields: &mut [PlayField], actions: &[Input]) -> Vec<(Observation, Reward, bool, Info)> {
+        play_fields
+            .iter_mut()
+            .zip(actions)
+            .map(|(play_field, &action)| Environment::step(play_field, action))
+            .collect()
+    }
+
+    #[cfg(feature = "parallel")]
+    fn step_all(play_fields: &mut [PlayField], actions: &[Input]) -> Vec<(Observation, Reward, bool, Info)> {
+        play_fields
+            .par_iter_mut()
+            .zip(actions.par_iter())
+            .map(|(play_field, &action)| Environment::step(play_field, action))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{Bullet, GameObj, GameStatus, Player, Position};
+    use crate::alien::Alien;
+
+    use super::*;
+
+    fn checksums(env: &VecEnv) -> Vec<u64> {
+        env.play_fields().iter().map(PlayField::checksum).collect()
+    }
+
+    fn play(env: &mut VecEnv, steps: usize) {
+        for tick in 0..steps {
+            let actions = (0..env.len())
+                .map(|i| Input::from_index((tick / 4 + i) % Input::COUNT).unwrap())
+                .collect::<Vec<_>>();
+            env.step(&actions);
+        }
+    }
+
+    #[test]
+    fn finished_games_are_reset() {
+        let config = GameConfig {
+            alien_rows: 1,
+            alien_columns: 1,
+            max_waves: Some(1),
+            ..GameConfig::default()
+        };
+        let mut env = VecEnv::with_config(2, 0, config);
+        let alien = env.play_fields[0].aliens().position();
+        env.play_fields[0].world.bullets = vec![Bullet::player_at_position(
+            Position { x: alien.x + Alien::WIDTH / 2, y: alien.y + Alien::HEIGHT },
+            Player::One,
+        )];
+
+        let step = env.step(&[Input::from_index(0).unwrap(); 2]);
+
+        assert_eq!(step.dones, [true, false]);
+        assert_eq!(step.infos[0].status, GameStatus::Won);
+        assert!(step.infos[0].score > 0);
+        assert_eq!(env.play_fields[0].score(), 0);
+        assert_eq!(env.play_fields[0].aliens().alive(), 1);
+        assert_eq!(step.observations[0].aliens.len(), 1);
+        assert!(!env.play_fields[0].status().is_done());
+    }
+
+    #[test]
+    fn same_batch_seed_gives_the_same_games() {
+        let mut env = VecEnv::new(3, 9);
+        let mut same = VecEnv::new(3, 9);
+        play(&mut env, 200);
+        play(&mut same, 200);
+        assert_eq!(checksums(&env), checksums(&same));
+
+        let mut other = VecEnv::new(3, 10);
+        play(&mut other, 200);
+        assert_ne!(checksums(&env), checksums(&other));
+
+        env.reset(9);
+        assert_eq!(checksums(&env), checksums(&VecEnv::new(3, 9)));
+    }
+
+    #[test]
+    #[should_panic(expected = "there has to be one action per game")]
+    fn one_action_per_game_is_required() {
+        let mut env = VecEnv::new(2, 0);
+        env.step(&[Input::from_index(0).unwrap()]);
+    }
+}
@@ -0,0 +1,364 @@
+//! Two players defending against one invasion on a shared field
+//!
+//! Every bullet belongs to the player, that fired it, so kills, misses and shot down bullets
+//! are scored for the right player. A player, that lost all lives, is eliminated, while the
+//! other player keeps playing.
+
+use alloc::vec::{Drain, Vec};
+use core::hash::{Hash, Hasher};
+
+use game::{Canvas, Game};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{GameObj, GameStatus, Input, PlayField, Player, Position, Score, raster};
+use crate::alien::{Alien, AlienType, Aliens};
+use crate::bullet::{Bullet, BulletDirection};
+use crate::bunker::Bunkers;
+use crate::cannon::Cannon;
+use crate::config::GameConfig;
+use crate::event::Event;
+use crate::replay::Fnv1a;
+use crate::world::World;
+
+pub use crate::world::Contender;
+
+/// A game, in which two cannons share one field
+///
+/// Both players have their own score and their own [`GameConfig::lives`]. The game ends, when
+/// both players are eliminated, the aliens invade, or the last of the
+/// [`GameConfig::max_waves`] is cleared. The [`Outcome`] only depends on the seed and the inputs.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Versus {
+    world: World,
+    players: [Contender; 2],
+    tick: usize,
+    status: GameStatus,
+}
+
+/// The result of a finished [`Versus`] game
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Outcome {
+    Winner(Player),
+    Draw,
+}
+
+/// Everything the agents can see of a [`Versus`] game
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Observation {
+    /// The cannons, indexed by [`Player::index`], or `None` while a cannon is off the field
+    pub cannons: [Option<Position>; 2],
+    pub aliens: Vec<(AlienType, Position)>,
+    pub mystery: Option<Position>,
+    pub bullets: Vec<(Position, BulletDirection)>,
+    pub bunkers: Vec<Position>,
+    /// Indexed by [`Player::index`]
+    pub lives: [usize; 2],
+    /// Indexed by [`Player::index`]
+    pub scores: [Score; 2],
+    pub wave: usize,
+    /// Whether the cannon of each player fires, if it is told to shoot in the next step
+    pub can_fire: [bool; 2],
+}
+
+// player one starts at one third of the field, player two at two thirds
+fn spawn(player: Player) -> Cannon {
+    Cannon::centered_at(PlayField::WIDTH * (player.index() + 1) / 3)
+}
+
+/// A game with the default rules and a random seed
+impl Default for Versus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Versus {
+    /// Creates a game with the default rules and a random seed
+    pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    /// Creates a game with the default rules, whose randomness is fully determined by `seed`
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_seed_and_config(seed, GameConfig::default())
+    }
+
+    /// Creates a game with the given rules, whose randomness is fully determined by `seed`
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the config is [invalid](GameConfig::validate).
+    pub fn with_seed_and_config(seed: u64, config: GameConfig) -> Self {
+        Self {
+            world: World::new(seed, config),
+            players: [
+                Contender::new(spawn(Player::One), config.lives),
+                Contender::new(spawn(Player::Two), config.lives),
+            ],
+            tick: 0,
+            status: GameStatus::Running,
+        }
+    }
+
+    /// A hash of the whole game state, that is identical on every platform
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+
+        self.world.hash(&mut hasher);
+        self.players.hash(&mut hasher);
+        self.tick.hash(&mut hasher);
+        self.status.hash(&mut hasher);
+
+        hasher.finish()
+    }
+
+    pub(crate) fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn aliens(&self) -> &Aliens {
+        &self.world.aliens
+    }
+
+    pub fn mystery(&self) -> Option<&Alien> {
+        self.world.mystery.as_ref()
+    }
+
+    pub fn bunkers(&self) -> &Bunkers {
+        &self.world.bunkers
+    }
+
+    pub fn bullets(&self) -> &[Bullet] {
+        &self.world.bullets
+    }
+
+    pub fn player(&self, player: Player) -> &Contender {
+        &self.players[player.index()]
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.world.seed
+    }
+
+    /// The current wave, starting at 1
+    pub fn wave(&self) -> usize {
+        self.world.wave
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.world.config
+    }
+
+    /// The status returned by the last [`Versus::step`]
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    /// The events, that happened since the events were drained the last time
+    pub fn events(&self) -> &[Event] {
+        &self.world.events
+    }
+
+    /// Removes and returns all collected events
+    pub fn drain_events(&mut self) -> Drain<'_, Event> {
+        self.world.events.drain(..)
+    }
+
+    /// Whether the cannon of `player` fires, if it is told to shoot in the next step
+    pub fn can_fire(&self, player: Player) -> bool {
+        let contender = self.player(player);
+        contender.is_active() && self.world.can_fire(contender, player)
+    }
+
+    pub fn observe(&self) -> Observation {
+        let [one, two] = &self.players;
+        let cannon = |contender: &Contender| match contender.is_active() {
+            true => Some(contender.cannon.position()),
+            false => None,
+        };
+
+        Observation {
+            cannons: [cannon(one), cannon(two)],
+            aliens: self.world.observe_aliens(),
+            mystery: self.mystery().map(|mystery| mystery.position()),
+            bullets: self.world.observe_bullets(),
+            bunkers: self.world.observe_bunkers(),
+            lives: [one.lives, two.lives],
+            scores: [one.score, two.score],
+            wave: self.wave(),
+            can_fire: [self.can_fire(Player::One), self.can_fire(Player::Two)],
+        }
+    }
+
+    /// The result of the game, or `None` while it is still running
+    ///
+    /// The player with the higher score wins. Ties are broken by the remaining lives, and then by
+    /// the number of survived steps.
+    pub fn outcome(&self) -> Option<Outcome> {
+        if !self.status.is_done() {
+            return None;
+        }
+
+        let [one, two] = &self.players;
+        let key = |contender: &Contender| (contender.score, contender.lives, contender.steps_survived);
+        Some(match key(one).cmp(&key(two)) {
+            core::cmp::Ordering::Greater => Outcome::Winner(Player::One),
+            core::cmp::Ordering::Less => Outcome::Winner(Player::Two),
+            core::cmp::Ordering::Equal => Outcome::Draw,
+        })
+    }
+
+    /// Advances the game by one step, player one with `inputs[0]` and player two with `inputs[1]`
+    ///
+    /// The inputs of eliminated or respawning players are ignored.
+    pub fn step(&mut self, inputs: [Input; 2]) -> GameStatus {
+        if self.status.is_done() {
+            return self.status;
+        }
+        self.tick += 1;
+
+        for player in Player::BOTH {
+            let contender = &mut self.players[player.index()];
+            if contender.is_eliminated() { continue; }
+
+            contender.steps_survived += 1;
+            if contender.respawn_ticks_left > 0 {
+                contender.respawn_ticks_left -= 1;
+                continue;
+            }
+
+            self.world.control(contender, player, inputs[player.index()]);
+        }
+
+        // the aliens take turns aiming at the players
+        let preferred = Player::BOTH[self.tick % 2];
+        let target = [preferred, preferred.opponent()]
+            .iter()
+            .map(|&player| self.player(player))
+            .find(|contender| contender.is_active())
+            .map(|contender| contender.cannon.position().x + Cannon::WIDTH / 2);
+
+        let advance = self.world.advance(&mut self.players, target, None);
+
+        // a hit cannon leaves the field, while it respawns, but the game goes on for the opponent
+        for player in Player::BOTH {
+            let contender = &mut self.players[player.index()];
+            if advance.lives_lost[player.index()] && !contender.is_eliminated() {
+                contender.cannon = spawn(player);
+                contender.respawn_ticks_left = self.world.config.respawn_ticks;
+            }
+        }
+        self.world.bullets.extend(advance.alien_shots);
+
+        let eliminated = self.players.iter().all(Contender::is_eliminated);
+        self.status = self.world.status(eliminated, advance.lives_lost.contains(&true));
+        self.status
+    }
+}
+
+/// A head-to-head game, whose actions are the inputs of both players
+///
+/// Since the trait has no access to the events, they are dropped at every step.
+impl Game for Versus {
+    type Observation = Observation;
+    type Action = [Input; 2];
+    type Status = GameStatus;
+
+    const WIDTH: usize = PlayField::WIDTH;
+    const HEIGHT: usize = PlayField::HEIGHT;
+
+    fn step(&mut self, action: Self::Action) -> Self::Status {
+        self.world.events.clear();
+        Versus::step(self, action)
+    }
+
+    fn status(&self) -> Self::Status {
+        self.status
+    }
+
+    fn observe(&self) -> Self::Observation {
+        Versus::observe(self)
+    }
+
+    fn players(&self) -> usize {
+        2
+    }
+
+    fn score(&self, player: usize) -> Score {
+        self.players[player].score
+    }
+
+    fn render(&self, canvas: &mut dyn Canvas) {
+        raster::draw_versus(self, canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Instruction;
+
+    const IDLE: Input = Input { instruction: Instruction::None, shoot: false };
+
+    // a deterministic, but busy, sequence of inputs
+    fn inputs(tick: usize) -> [Input; 2] {
+        let input = |index: usize| Input::from_index(index % Input::COUNT).unwrap();
+        [input(tick / 7), input(tick / 5 + 1)]
+    }
+
+    fn play(seed: u64) -> Versus {
+        let config = GameConfig {
+            lives: 1,
+            max_waves: Some(1),
+            ..GameConfig::default()
+        };
+        let mut versus = Versus::with_seed_and_config(seed, config);
+        for tick in 0..20_000 {
+            if versus.step(inputs(tick)).is_done() {
+                break;
+            }
+        }
+
+        versus
+    }
+
+    #[test]
+    fn outcome_only_depends_on_seed_and_inputs() {
+        let first = play(3);
+        let second = play(3);
+
+        assert!(first.outcome().is_some());
+        assert_eq!(first.outcome(), second.outcome());
+        assert_eq!(first.checksum(), second.checksum());
+    }
+
+    #[test]
+    fn ties_are_broken_by_lives_then_by_survival() {
+        let mut versus = Versus::with_seed(1);
+        versus.status = GameStatus::Won;
+        assert_eq!(versus.outcome(), Some(Outcome::Draw));
+
+        versus.players[0].lives -= 1;
+        assert_eq!(versus.outcome(), Some(Outcome::Winner(Player::Two)));
+
+        versus.players[1].lives -= 1;
+        versus.players[0].steps_survived += 1;
+        assert_eq!(versus.outcome(), Some(Outcome::Winner(Player::One)));
+
+        versus.players[1].score += 1;
+        assert_eq!(versus.outcome(), Some(Outcome::Winner(Player::Two)));
+    }
+
+    #[test]
+    fn running_game_has_no_outcome() {
+        let mut versus = Versus::with_seed(1);
+        versus.step([IDLE; 2]);
+
+        assert_eq!(versus.outcome(), None);
+    }
+}
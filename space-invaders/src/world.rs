@@ -0,0 +1,590 @@
+//! The part of a game, that doesn't depend on the number of players
+//!
+//! [`PlayField`] and [`Versus`](crate::versus::Versus) both run on a [`World`] and only differ in
+//! how they handle their [`Contender`]s, e.g. whether a hit cannon freezes the whole game.
+
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+
+use rand::SeedableRng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Bullet, GameObj, GameOverReason, GameRng, GameStatus, GetHit, Input, Instruction, PlayField, Player, Position, Score, Step, Unit, WouldHit};
+use crate::alien::{Alien, AlienType, Aliens};
+use crate::bullet::BulletDirection;
+use crate::bunker::Bunkers;
+use crate::cannon::Cannon;
+use crate::config::GameConfig;
+use crate::event::Event;
+
+/// Everything on the field, that doesn't belong to a player
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) struct World {
+    pub(crate) aliens: Aliens,
+    pub(crate) mystery: Option<Alien>,
+    pub(crate) steps_until_mystery: usize,
+    pub(crate) bunkers: Bunkers,
+    pub(crate) bullets: Vec<Bullet>,
+    pub(crate) wave: usize,
+
+    pub(crate) config: GameConfig,
+    pub(crate) seed: u64,
+    pub(crate) rng: GameRng,
+
+    pub(crate) events: Vec<Event>,
+}
+
+/// The cannon, score and lives of one player
+#[derive(Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Contender {
+    pub(crate) cannon: Cannon,
+    pub(crate) score: Score,
+    pub(crate) lives: usize,
+    pub(crate) respawn_ticks_left: usize,
+    pub(crate) steps_survived: usize,
+}
+
+/// What happened to the players during [`World::advance`]
+pub(crate) struct Advance {
+    /// Indexed by [`Player::index`]
+    pub(crate) lives_lost: [bool; 2],
+    /// The bullets the aliens fired, which are not on the field yet
+    pub(crate) alien_shots: Vec<Bullet>,
+}
+
+impl Contender {
+    pub(crate) fn new(cannon: Cannon, lives: usize) -> Self {
+        Self {
+            cannon,
+            score: 0,
+            lives,
+            respawn_ticks_left: 0,
+            steps_survived: 0,
+        }
+    }
+
+    pub fn cannon(&self) -> &Cannon {
+        &self.cannon
+    }
+
+    pub fn score(&self) -> Score {
+        self.score
+    }
+
+    pub fn lives(&self) -> usize {
+        self.lives
+    }
+
+    /// The number of steps the cannon stays off the field, until it respawns
+    pub fn respawn_ticks_left(&self) -> usize {
+        self.respawn_ticks_left
+    }
+
+    /// The number of steps, in which the player was not eliminated
+    pub fn steps_survived(&self) -> usize {
+        self.steps_survived
+    }
+
+    pub fn is_eliminated(&self) -> bool {
+        self.lives == 0
+    }
+
+    /// Whether the cannon is on the field, i.e. can move, fire and be hit
+    pub(crate) fn is_active(&self) -> bool {
+        !self.is_eliminated() && self.respawn_ticks_left == 0
+    }
+}
+
+impl World {
+    /// # Panics
+    ///
+    /// Panics, if the config is [invalid](GameConfig::validate).
+    pub(crate) fn new(seed: u64, config: GameConfig) -> Self {
+        if let Err(err) = config.validate() {
+            panic!("{}", err);
+        }
+
+        Self {
+            aliens: Aliens::new(&config),
+            mystery: None,
+            steps_until_mystery: config.mystery_interval,
+            bunkers: Bunkers::new(&config),
+            bullets: Vec::new(),
+            wave: 1,
+            config,
+            seed,
+            rng: GameRng::seed_from_u64(seed),
+            events: Vec::new(),
+        }
+    }
+
+    /// Whether the cannon of `player` fires, if it is told to shoot in the next step
+    pub(crate) fn can_fire(&self, contender: &Contender, player: Player) -> bool {
+        let player_bullets = self.bullets
+            .iter()
+            .filter(|bullet| bullet.player() == Some(player))
+            .count();
+
+        contender.cannon.cooldown() == 0
+            && !matches!(self.config.max_player_bullets, Some(max_bullets) if player_bullets >= max_bullets)
+    }
+
+    /// Moves the cannon of `player` and fires, if it is told to and able to
+    pub(crate) fn control(&mut self, contender: &mut Contender, player: Player, input: Input) {
+        let can_fire = self.can_fire(contender, player);
+
+        match input.instruction {
+            Instruction::MoveRight => contender.cannon.move_right(self.config.cannon_speed),
+            Instruction::MoveLeft => contender.cannon.move_left(self.config.cannon_speed),
+            Instruction::None => {}
+        }
+
+        if input.shoot && can_fire {
+            let bullet = contender.cannon.shoot(self.config.fire_cooldown, player);
+            self.events.push(Event::ShotFired { player, position: bullet.position() });
+            self.bullets.push(bullet);
+        } else {
+            contender.cannon.cool_down();
+        }
+    }
+
+    /// Advances the aliens and the bullets, and resolves every hit
+    ///
+    /// `contenders` are indexed by [`Player::index`]. The aliens aim at the x coordinate `target`.
+    /// Alien bullets are scored for `alien_bullet_scorer`, i.e. in a single player game the player
+    /// is charged for every bullet, that leaves the field.
+    pub(crate) fn advance(&mut self, contenders: &mut [Contender], target: Option<Unit>, alien_bullet_scorer: Option<Player>) -> Advance {
+        if let Some(target) = target {
+            self.aliens.aim_at(target);
+        }
+
+        self.aliens.step(&mut self.rng);
+        let alien_shots = self.aliens.shoot(&self.config.shoot_probability, &mut self.rng);
+        // the formation erases every bunker pixel it touches, while it descends
+        for alien in self.aliens.iter().flatten().flatten() {
+            self.bunkers.erase(alien.position(), Alien::WIDTH, Alien::HEIGHT);
+        }
+        self.step_mystery();
+
+        let scorer = |bullet: &Bullet| bullet.player().or(alien_bullet_scorer);
+        let rng = &mut self.rng;
+        let events = &mut self.events;
+
+        self.bullets.drain_filter(|bullet| {
+            // bullet is out of field
+            if !bullet.step(rng).survived || !PlayField::overlaps(bullet) {
+                if let Some(player) = scorer(bullet) {
+                    contenders[player.index()].score -= 1;
+                }
+                events.push(Event::BulletExpired { player: bullet.player(), position: bullet.position() });
+                return true;
+            }
+
+            false
+        });
+
+        if self.config.bullet_collisions {
+            self.collide_bullets(contenders);
+        }
+
+        let mut lives_lost = [false; 2];
+        let aliens = &mut self.aliens;
+        let mystery = &mut self.mystery;
+        let bunkers = &mut self.bunkers;
+        let rng = &mut self.rng;
+        let events = &mut self.events;
+
+        self.bullets.drain_filter(|bullet| {
+            // bullet hit a Cannon
+            // every cannon can only lose one life per step
+            for (contender, player) in contenders.iter_mut().zip(Player::BOTH) {
+                if contender.is_active() && !lives_lost[player.index()] && contender.cannon.would_hit(bullet).is_some() {
+                    contender.lives -= 1;
+                    lives_lost[player.index()] = true;
+                    events.push(Event::CannonHit { player, position: bullet.position(), lives_left: contender.lives });
+                    return true;
+                }
+            }
+
+            let mut no_score = 0;
+            let score = match scorer(bullet) {
+                Some(player) => &mut contenders[player.index()].score,
+                None => &mut no_score,
+            };
+
+            // bullet hit the mystery ship
+            if mystery.as_mut().and_then(|m| m.would_hit(bullet)).is_some()
+                && Self::hit_alien(mystery, bullet, score, events, rng) {
+                return true;
+            }
+
+            // bullet hit an Alien
+            if let Some(alien) = aliens.would_hit(bullet) {
+                if Self::hit_alien(alien, bullet, score, events, rng) {
+                    return true;
+                }
+            }
+
+            // bullet hit Bunker
+            if let Some(bunker) = bunkers.would_hit(bullet) {
+                if bunker.hit(bullet, score, rng).absorbed_bullet {
+                    events.push(Event::BunkerHit { player: bullet.player(), position: bullet.directional_position() });
+                    return true;
+                }
+            }
+
+            false
+        });
+
+        Advance {
+            lives_lost,
+            alien_shots,
+        }
+    }
+
+    /// The status after a step, in which every player was `eliminated` or not, and any player
+    /// `lost_life` or not
+    pub(crate) fn status(&mut self, eliminated: bool, lost_life: bool) -> GameStatus {
+        if eliminated {
+            GameStatus::GameOver { reason: GameOverReason::NoLivesLeft }
+        } else if self.aliens.invaded() {
+            GameStatus::GameOver { reason: GameOverReason::Invaded }
+        } else if self.aliens.alive() == 0 {
+            self.next_wave()
+        } else if lost_life {
+            GameStatus::LifeLost
+        } else {
+            GameStatus::Running
+        }
+    }
+
+    /// Destroys every player bullet, that overlaps an alien bullet, and vice versa
+    ///
+    /// A bullet is destroyed, if it overlaps any opposing bullet, so the outcome does not depend
+    /// on the order of the bullets. An alien bullet is credited to every player, whose bullet
+    /// overlaps it.
+    fn collide_bullets(&mut self, contenders: &mut [Contender]) {
+        let opposing = |bullet: &Bullet, other: &Bullet| {
+            bullet.is_alien_bullet() != other.is_alien_bullet() && bullet.overlaps(other)
+        };
+
+        // mark
+        let collided = self.bullets
+            .iter()
+            .map(|bullet| self.bullets.iter().any(|other| opposing(bullet, other)))
+            .collect::<Vec<_>>();
+
+        for alien_bullet in self.bullets.iter().filter(|bullet| bullet.is_alien_bullet()) {
+            for (contender, player) in contenders.iter_mut().zip(Player::BOTH) {
+                let shot_down = self.bullets
+                    .iter()
+                    .any(|bullet| bullet.player() == Some(player) && opposing(alien_bullet, bullet));
+                if shot_down {
+                    contender.score += self.config.bullet_collision_score;
+                    self.events.push(Event::BulletShotDown { player, position: alien_bullet.position() });
+                }
+            }
+        }
+
+        // sweep
+        let mut collided = collided.into_iter();
+        self.bullets.retain(|_| !collided.next().unwrap_or(false));
+    }
+
+    /// Returns whether the bullet was absorbed
+    pub(crate) fn hit_alien(alien: &mut Option<Alien>, bullet: &Bullet, score: &mut Score, events: &mut Vec<Event>, rng: &mut GameRng) -> bool {
+        let (alien_type, position) = match alien {
+            Some(alien) => (alien.alien_type(), alien.position()),
+            None => return false,
+        };
+
+        let score_before = *score;
+        let hr = alien.hit(bullet, score, rng);
+        if let (false, Some(player)) = (hr.survived, bullet.player()) {
+            events.push(Event::AlienKilled { player, alien_type, position, points: *score - score_before });
+        }
+
+        hr.absorbed_bullet
+    }
+
+    fn next_wave(&mut self) -> GameStatus {
+        self.events.push(Event::WaveCleared { wave: self.wave });
+        if matches!(self.config.max_waves, Some(max_waves) if self.wave >= max_waves) {
+            return GameStatus::Won;
+        }
+
+        self.wave += 1;
+        self.aliens = Aliens::for_wave(&self.config, self.wave);
+        GameStatus::WaveCleared
+    }
+
+    fn step_mystery(&mut self) {
+        match &mut self.mystery {
+            Some(mystery) => {
+                // the mystery ship left the PlayField
+                if !mystery.step(&mut self.rng).survived {
+                    self.mystery = None;
+                }
+            }
+            None => {
+                self.steps_until_mystery = self.steps_until_mystery.saturating_sub(1);
+                if self.steps_until_mystery == 0 {
+                    let mystery = Alien::mystery();
+                    self.events.push(Event::MysterySpawned { position: mystery.position() });
+                    self.mystery = Some(mystery);
+                    self.steps_until_mystery = self.config.mystery_interval;
+                }
+            }
+        }
+    }
+
+    pub(crate) fn observe_aliens(&self) -> Vec<(AlienType, Position)> {
+        self.aliens
+            .iter()
+            .flat_map(|col| col.iter())
+            .flatten()
+            .map(|alien| (alien.alien_type(), alien.position()))
+            .collect()
+    }
+
+    pub(crate) fn observe_bullets(&self) -> Vec<(Position, BulletDirection)> {
+        self.bullets
+            .iter()
+            .map(|bullet| (bullet.position(), bullet.direction()))
+            .collect()
+    }
+
+    pub(crate) fn observe_bunkers(&self) -> Vec<Position> {
+        self.bunkers
+            .iter()
+            .flatten()
+            .map(|bunker| bunker.position())
+            .collect()
+    }
+}
+
+// the events don't influence the game
+impl Hash for World {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.aliens.hash(state);
+        self.mystery.hash(state);
+        self.steps_until_mystery.hash(state);
+        self.bunkers.hash(state);
+        self.bullets.hash(state);
+        self.wave.hash(state);
+        self.config.hash(state);
+        self.seed.hash(state);
+        self.rng.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn contenders() -> [Contender; 2] {
+        [Contender::new(Cannon::new(), 3), Contender::new(Cannon::new(), 3)]
+    }
+
+    fn positions(bullets: &[Bullet]) -> Vec<Position> {
+        bullets.iter().map(Bullet::position).collect()
+    }
+
+    #[test]
+    fn crossing_bullets_collide_independent_of_their_order() {
+        let crossing = Position { x: 50, y: 100 };
+        let bullets = vec![
+            Bullet::alien_at_position(crossing, AlienType::Easy),
+            Bullet::player_at_position(Position { x: 50, y: 101 }, Player::One),
+            Bullet::player_at_position(Position { x: 50, y: 102 }, Player::Two),
+            Bullet::alien_at_position(Position { x: 150, y: 50 }, AlienType::Hard),
+            Bullet::player_at_position(Position { x: 200, y: 120 }, Player::One),
+        ];
+        let mut reversed = bullets.clone();
+        reversed.reverse();
+
+        let mut forwards = World::new(0, GameConfig::default());
+        forwards.bullets = bullets;
+        let mut forwards_contenders = contenders();
+        forwards.collide_bullets(&mut forwards_contenders);
+
+        let mut backwards = World::new(0, GameConfig::default());
+        backwards.bullets = reversed;
+        let mut backwards_contenders = contenders();
+        backwards.collide_bullets(&mut backwards_contenders);
+
+        let mut survivors = positions(&backwards.bullets);
+        survivors.reverse();
+        assert_eq!(positions(&forwards.bullets), survivors);
+        assert_eq!(survivors, [Position { x: 150, y: 50 }, Position { x: 200, y: 120 }]);
+
+        let shot_down = [
+            Event::BulletShotDown { player: Player::One, position: crossing },
+            Event::BulletShotDown { player: Player::Two, position: crossing },
+        ];
+        assert_eq!(forwards.events, shot_down);
+        assert_eq!(backwards.events, shot_down);
+
+        for (forwards, backwards) in forwards_contenders.iter().zip(&backwards_contenders) {
+            assert_eq!(forwards.score, GameConfig::default().bullet_collision_score);
+            assert_eq!(forwards.score, backwards.score);
+        }
+    }
+
+    const SHOOT: Input = Input { instruction: Instruction::None, shoot: true };
+
+    fn player_bullets(world: &World) -> usize {
+        world.bullets.iter().filter(|bullet| !bullet.is_alien_bullet()).count()
+    }
+
+    #[test]
+    fn max_player_bullets_blocks_further_shots() {
+        let mut world = World::new(0, GameConfig::default());
+        let [mut contender, _] = contenders();
+
+        world.control(&mut contender, Player::One, SHOOT);
+        assert_eq!(player_bullets(&world), 1);
+        assert!(!world.can_fire(&contender, Player::One));
+
+        world.control(&mut contender, Player::One, SHOOT);
+        assert_eq!(player_bullets(&world), 1);
+
+        world.bullets.clear();
+        assert!(world.can_fire(&contender, Player::One));
+        world.control(&mut contender, Player::One, SHOOT);
+        assert_eq!(player_bullets(&world), 1);
+    }
+
+    #[test]
+    fn max_player_bullets_are_counted_per_player() {
+        let mut world = World::new(0, GameConfig::default());
+        let [mut one, mut two] = contenders();
+
+        world.control(&mut one, Player::One, SHOOT);
+        world.control(&mut two, Player::Two, SHOOT);
+        assert_eq!(player_bullets(&world), 2);
+    }
+
+    #[test]
+    fn fire_cooldown_blocks_shots_for_exactly_its_steps() {
+        const COOLDOWN: usize = 3;
+        let mut world = World::new(0, GameConfig {
+            fire_cooldown: COOLDOWN,
+            max_player_bullets: None,
+            ..GameConfig::default()
+        });
+        let [mut contender, _] = contenders();
+
+        world.control(&mut contender, Player::One, SHOOT);
+        assert_eq!(player_bullets(&world), 1);
+
+        for _ in 0..COOLDOWN {
+            assert!(!world.can_fire(&contender, Player::One));
+            world.control(&mut contender, Player::One, SHOOT);
+            assert_eq!(player_bullets(&world), 1);
+        }
+
+        assert!(world.can_fire(&contender, Player::One));
+        world.control(&mut contender, Player::One, SHOOT);
+        assert_eq!(player_bullets(&world), 2);
+    }
+
+    fn mystery_world(interval: usize) -> World {
+        World::new(0, GameConfig {
+            mystery_interval: interval,
+            ..GameConfig::default()
+        })
+    }
+
+    #[test]
+    fn mystery_spawns_after_the_interval() {
+        let mut world = mystery_world(5);
+
+        for _ in 1..5 {
+            world.step_mystery();
+            assert!(world.mystery.is_none());
+        }
+        world.step_mystery();
+
+        let position = Alien::mystery().position();
+        assert_eq!(world.mystery.as_ref().map(Alien::position), Some(position));
+        assert_eq!(world.events, [Event::MysterySpawned { position }]);
+    }
+
+    #[test]
+    fn mystery_despawns_after_leaving_the_field_and_the_interval_restarts() {
+        let mut world = mystery_world(5);
+        for _ in 0..5 {
+            world.step_mystery();
+        }
+
+        for _ in 1..PlayField::WIDTH {
+            world.step_mystery();
+            assert!(world.mystery.is_some());
+        }
+        world.step_mystery();
+        assert!(world.mystery.is_none());
+
+        for _ in 1..5 {
+            world.step_mystery();
+            assert!(world.mystery.is_none());
+        }
+        world.step_mystery();
+        assert!(world.mystery.is_some());
+        assert_eq!(world.events.len(), 2);
+    }
+
+    #[test]
+    fn hitting_the_mystery_scores_points() {
+        let mut world = mystery_world(600);
+        world.mystery = Some(Alien::mystery());
+        world.bullets = vec![Bullet::player_at_position(Position { x: 5, y: 6 }, Player::One)];
+        let mut contenders = contenders();
+
+        world.advance(&mut contenders, None, None);
+
+        assert!(world.mystery.is_none());
+        assert!(world.bullets.iter().all(Bullet::is_alien_bullet));
+        let points = contenders[Player::One.index()].score;
+        assert!((10..100).contains(&points));
+        assert!(world.events.contains(&Event::AlienKilled {
+            player: Player::One,
+            alien_type: AlienType::Mystery,
+            position: Position { x: 1, y: 0 },
+            points,
+        }));
+    }
+
+    #[test]
+    fn cleared_waves_are_counted() {
+        let config = GameConfig::default();
+        let mut world = World::new(0, config);
+        assert_eq!(world.wave, 1);
+
+        for wave in 2..=4 {
+            assert_eq!(world.next_wave(), GameStatus::WaveCleared);
+            assert_eq!(world.wave, wave);
+            assert_eq!(world.aliens.position(), Aliens::for_wave(&config, wave).position());
+        }
+        assert_eq!(world.events.last(), Some(&Event::WaveCleared { wave: 3 }));
+    }
+
+    #[test]
+    fn clearing_the_last_wave_wins() {
+        let config = GameConfig {
+            max_waves: Some(2),
+            ..GameConfig::default()
+        };
+        let mut world = World::new(0, config);
+
+        assert_eq!(world.next_wave(), GameStatus::WaveCleared);
+        assert_eq!(world.next_wave(), GameStatus::Won);
+        assert_eq!(world.wave, 2);
+        assert_eq!(world.events.last(), Some(&Event::WaveCleared { wave: 2 }));
+    }
+}